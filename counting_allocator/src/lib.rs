@@ -0,0 +1,86 @@
+//! Allocator which forwards to the system `malloc` and counts allocations made on each thread.
+//! Linking this crate replaces the default allocator for the whole binary.
+
+#![feature(allocator, thread_local)]
+#![allocator]
+#![no_std]
+
+use core::ptr;
+
+// Alignment which `malloc` is guaranteed to satisfy on x86_64.
+const MIN_ALIGN: usize = 16;
+
+#[thread_local]
+static mut ALLOCATIONS: usize = 0;
+
+extern "C" {
+    fn malloc(size: usize) -> *mut u8;
+    fn realloc(ptr: *mut u8, size: usize) -> *mut u8;
+    fn free(ptr: *mut u8);
+    fn posix_memalign(ptr: *mut *mut u8, align: usize, size: usize) -> i32;
+}
+
+/// Returns the number of allocations and reallocations made so far on the calling thread.
+pub fn allocations() -> usize {
+    unsafe { ALLOCATIONS }
+}
+
+unsafe fn allocate(size: usize, align: usize) -> *mut u8 {
+    if align <= MIN_ALIGN {
+        malloc(size)
+    } else {
+        let mut out = ptr::null_mut();
+        if posix_memalign(&mut out, align, size) == 0 {
+            out
+        } else {
+            ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn __rust_allocate(size: usize, align: usize) -> *mut u8 {
+    unsafe {
+        ALLOCATIONS += 1;
+        allocate(size, align)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn __rust_deallocate(ptr: *mut u8, _old_size: usize, _align: usize) {
+    unsafe { free(ptr) }
+}
+
+#[no_mangle]
+pub extern "C" fn __rust_reallocate(ptr: *mut u8,
+                                    old_size: usize,
+                                    size: usize,
+                                    align: usize)
+                                    -> *mut u8 {
+    unsafe {
+        ALLOCATIONS += 1;
+        if align <= MIN_ALIGN {
+            return realloc(ptr, size);
+        }
+        let new_ptr = allocate(size, align);
+        if !new_ptr.is_null() {
+            ptr::copy(ptr, new_ptr, if old_size < size { old_size } else { size });
+            free(ptr);
+        }
+        new_ptr
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn __rust_reallocate_inplace(_ptr: *mut u8,
+                                            old_size: usize,
+                                            _size: usize,
+                                            _align: usize)
+                                            -> usize {
+    old_size
+}
+
+#[no_mangle]
+pub extern "C" fn __rust_usable_size(size: usize, _align: usize) -> usize {
+    size
+}
@@ -0,0 +1,53 @@
+//! Converts a bincode-encoded `Document` to another codec.
+//!
+//! Usage: `cargo run --example roundtrip <codec> <input> <output>`
+
+extern crate serialization_evaluation;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::process;
+use serialization_evaluation::{codec, Document};
+
+fn fail(message: &str) -> ! {
+    let _ = writeln!(io::stderr(), "{}", message);
+    process::exit(1)
+}
+
+fn codec_names() -> String {
+    let names: Vec<_> = codec::all::<Document>().iter().map(|codec| codec.name()).collect();
+    names.join(", ")
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 4 {
+        fail(&format!("Usage: {} <codec> <input> <output>\nCodecs: {}", args[0], codec_names()));
+    }
+
+    let output_codec = match codec::by_name::<Document>(&args[1]) {
+        Some(codec) => codec,
+        None => fail(&format!("Unknown codec '{}'.  Valid codecs: {}", args[1], codec_names())),
+    };
+
+    let mut input = Vec::new();
+    if let Err(error) = File::open(&args[2]).and_then(|mut file| file.read_to_end(&mut input)) {
+        fail(&format!("Failed to read {}: {}", args[2], error));
+    }
+    let document = match codec::by_name::<Document>("serde_and_bincode").unwrap().decode(&input) {
+        Ok(document) => document,
+        Err(error) => fail(&format!("Failed to decode {}: {}", args[2], error)),
+    };
+
+    let output = match output_codec.encode(&document) {
+        Ok(output) => output,
+        Err(error) => fail(&format!("Failed to encode with {}: {}", output_codec.name(), error)),
+    };
+    if let Err(error) = File::create(&args[3]).and_then(|mut file| file.write_all(&output)) {
+        fail(&format!("Failed to write {}: {}", args[3], error));
+    }
+
+    println!("Input:  {} bytes", input.len());
+    println!("Output: {} bytes using {}", output.len(), output_codec.name());
+}
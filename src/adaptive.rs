@@ -0,0 +1,140 @@
+use codec::Codec;
+use {Document, EvalError};
+
+const UNCOMPRESSED: u8 = 0;
+const DEFLATED: u8 = 1;
+
+// Enough of the content to estimate its entropy, without counting every byte of a big document.
+const ENTROPY_SAMPLE_SIZE: usize = 64 * 1024;
+
+// In bits per byte.  Content any more random than this won't deflate by enough to be worth it.
+const MAX_COMPRESSIBLE_ENTROPY: f64 = 7.0;
+
+/// Encodes `value` with each of `codecs` and returns the id of the codec which gave the smallest
+/// output along with that output.  Ties go to the earliest codec in the list.
+pub fn encode_smallest<T>(value: &T, codecs: &[&Codec<T>]) -> Result<(u8, Vec<u8>), EvalError> {
+    let mut smallest: Option<(u8, Vec<u8>)> = None;
+    for codec in codecs {
+        let bytes = try!(codec.encode(value));
+        let is_smaller = match smallest {
+            Some((_, ref current)) => bytes.len() < current.len(),
+            None => true,
+        };
+        if is_smaller {
+            smallest = Some((codec.id(), bytes));
+        }
+    }
+    smallest.ok_or(EvalError::NoCodecs)
+}
+
+/// Decodes output of `encode_smallest` using whichever of `codecs` has the id `tag`.
+pub fn decode_tagged<T>(tag: u8, bytes: &[u8], codecs: &[&Codec<T>]) -> Result<T, EvalError> {
+    match codecs.iter().find(|codec| codec.id() == tag) {
+        Some(codec) => codec.decode(bytes),
+        None => Err(EvalError::UnknownCodec(tag)),
+    }
+}
+
+/// Returns the Shannon entropy of `bytes` in bits per byte, from 0 for a single repeated value up
+/// to 8 for uniformly random data.
+fn entropy(bytes: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for &byte in bytes {
+        counts[byte as usize] += 1;
+    }
+    let total = bytes.len() as f64;
+    counts.iter()
+          .filter(|&&count| count > 0)
+          .fold(0.0, |entropy, &count| {
+              let probability = count as f64 / total;
+              entropy - probability * probability.log2()
+          })
+}
+
+/// Encodes `document` with bincode, deflating the output unless a sample of the content looks too
+/// random to compress.  The output starts with a flag byte saying which was done.
+pub fn encode_by_entropy(document: &Document) -> Vec<u8> {
+    let sample_size = ::std::cmp::min(document.content.len(), ENTROPY_SAMPLE_SIZE);
+    let payload = ::serde_and_bincode::encode(document);
+    let (flag, payload) = if entropy(&document.content[..sample_size]) > MAX_COMPRESSIBLE_ENTROPY {
+        (UNCOMPRESSED, payload)
+    } else {
+        (DEFLATED, ::compress::deflate_compress(&payload))
+    };
+    let mut bytes = Vec::with_capacity(payload.len() + 1);
+    bytes.push(flag);
+    bytes.extend_from_slice(&payload);
+    bytes
+}
+
+/// Decodes output of `encode_by_entropy`.  A few KiB of deflated input can inflate to gigabytes,
+/// so this fails rather than inflating the payload past `max_len` bytes.
+pub fn decode_by_entropy(bytes: &[u8], max_len: u64) -> Result<Document, EvalError> {
+    let payload = match bytes.split_first() {
+        Some((&UNCOMPRESSED, payload)) => return ::serde_and_bincode::decode_result(payload),
+        Some((&DEFLATED, payload)) => try!(::compress::deflate_decompress_result(payload, max_len)),
+        Some((&flag, _)) => return Err(EvalError::UnknownCompression(flag)),
+        None => return Err(EvalError::TruncatedHeader),
+    };
+    ::serde_and_bincode::decode_result(&payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_by_entropy, decode_tagged, encode_by_entropy, encode_smallest, DEFLATED,
+                UNCOMPRESSED};
+    use {codec, make_compressible_sample_data, make_sample_data, Document, EvalError};
+
+    const MAX_LEN: u64 = 2 * 1024 * 1024;
+
+    #[test]
+    fn picks_smallest() {
+        let codecs = codec::all::<Document>();
+        let small = make_sample_data(0);
+        let big = make_sample_data(1024 * 1024);
+
+        let (small_tag, small_bytes) = encode_smallest(&small, &codecs).unwrap();
+        let (big_tag, big_bytes) = encode_smallest(&big, &codecs).unwrap();
+        for codec in &codecs {
+            assert!(small_bytes.len() <= codec.encode(&small).unwrap().len());
+            assert!(big_bytes.len() <= codec.encode(&big).unwrap().len());
+        }
+        // CBOR's variable-length integers win for the small document, but it encodes each content
+        // byte above 23 as two bytes, so Bincode wins for the big one.
+        assert!(small_tag != big_tag);
+
+        assert_eq!(decode_tagged::<Document>(small_tag, &small_bytes, &codecs).unwrap(), small);
+        assert_eq!(decode_tagged::<Document>(big_tag, &big_bytes, &codecs).unwrap(), big);
+    }
+
+    #[test]
+    fn compresses_by_entropy() {
+        let random = make_sample_data(1024 * 1024);
+        let bytes = encode_by_entropy(&random);
+        assert_eq!(bytes[0], UNCOMPRESSED);
+        assert_eq!(&bytes[1..], &::serde_and_bincode::encode(&random)[..]);
+        assert_eq!(decode_by_entropy(&bytes, MAX_LEN).unwrap(), random);
+
+        let zeros = make_compressible_sample_data(1024 * 1024);
+        let bytes = encode_by_entropy(&zeros);
+        assert_eq!(bytes[0], DEFLATED);
+        assert!(bytes.len() < 1024 * 1024 / 100);
+        assert_eq!(decode_by_entropy(&bytes, MAX_LEN).unwrap(), zeros);
+    }
+
+    #[test]
+    fn too_long() {
+        let zeros = make_compressible_sample_data(1024 * 1024);
+        let bytes = encode_by_entropy(&zeros);
+        let len = ::serde_and_bincode::encode(&zeros).len() as u64;
+        assert_eq!(decode_by_entropy(&bytes, len).unwrap(), zeros);
+        assert_err!(decode_by_entropy(&bytes, len - 1),
+                    EvalError::DecompressedTooLong(limit) => assert_eq!(limit, len - 1));
+    }
+
+    #[test]
+    fn invalid_flag() {
+        assert_err!(decode_by_entropy(&[2, 0, 0], MAX_LEN), EvalError::UnknownCompression(2));
+        assert_err!(decode_by_entropy(&[], MAX_LEN), EvalError::TruncatedHeader);
+    }
+}
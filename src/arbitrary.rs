@@ -0,0 +1,62 @@
+use rand::Rng;
+use {Document, Person};
+
+// Large enough to cross every codec's length-prefix boundaries, which the ordinary lengths would
+// otherwise never reach.
+const HUGE_CONTENT: usize = 70 * 1024;
+
+// The longest string or vector otherwise generated.
+const MAX_LEN: usize = 100;
+
+fn arbitrary_string<R: Rng>(rng: &mut R) -> String {
+    let len = rng.gen_range(0, MAX_LEN + 1);
+    (0..len).map(|_| rng.gen::<char>()).collect()
+}
+
+pub fn arbitrary_person<R: Rng>(rng: &mut R) -> Person {
+    Person {
+        id: rng.gen(),
+        name: arbitrary_string(rng),
+        email: arbitrary_string(rng),
+    }
+}
+
+pub fn arbitrary_document<R: Rng>(rng: &mut R) -> Document {
+    let content_len = if rng.gen_weighted_bool(8) {
+        HUGE_CONTENT
+    } else {
+        rng.gen_range(0, MAX_LEN + 1)
+    };
+    let author_count = rng.gen_range(0, MAX_LEN + 1);
+    Document {
+        id: rng.gen(),
+        name: arbitrary_string(rng),
+        authors: (0..author_count).map(|_| arbitrary_person(rng)).collect(),
+        content: (0..content_len).map(|_| rng.gen()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand;
+    use super::arbitrary_document;
+    use Document;
+
+    const CASES: usize = 100;
+
+    #[test]
+    fn round_trips() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..CASES {
+            let document = arbitrary_document(&mut rng);
+            for codec in ::codec::all::<Document>() {
+                let bytes = codec.encode(&document).unwrap();
+                assert!(codec.decode(&bytes).ok().map_or(false, |decoded| decoded == document),
+                        "{} failed to round-trip a document with {} authors and {} content bytes",
+                        codec.name(),
+                        document.authors.len(),
+                        document.content.len());
+            }
+        }
+    }
+}
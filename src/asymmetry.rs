@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod tests {
+    use cold_cache::{mean_time, micros};
+    use {make_sample_data, Document};
+    use test::black_box;
+
+    const ITERATIONS: u32 = 20;
+
+    // Benches are reported in isolation, so this times both directions itself to relate them.
+    // Run with `cargo test --release encode_decode_asymmetry -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn encode_decode_asymmetry() {
+        println!("");
+        println!("Mean time to encode and decode the big document:");
+        let document = make_sample_data(1024 * 1024);
+        for codec in ::codec::all::<Document>() {
+            let bytes = codec.encode(&document).unwrap();
+            let encode = micros(mean_time(ITERATIONS, None, || {
+                black_box(codec.encode(&document).unwrap());
+            }));
+            let decode = micros(mean_time(ITERATIONS, None, || {
+                black_box(codec.decode(&bytes).unwrap());
+            }));
+            println!("Using {}", codec.name());
+            println!("    encode: {} us, decode: {} us, decode/encode: {:.2}",
+                     encode,
+                     decode,
+                     decode as f64 / encode as f64);
+        }
+    }
+}
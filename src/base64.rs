@@ -0,0 +1,61 @@
+use rustc_serialize::base64::{FromBase64, ToBase64, STANDARD};
+use codec::Codec;
+use EvalError;
+
+/// Encodes `v` with `codec` and then as padded base64 with the standard alphabet, for embedding
+/// in a text format.
+pub fn encode_base64<T>(codec: &Codec<T>, v: &T) -> Result<String, EvalError> {
+    Ok(try!(codec.encode(v)).to_base64(STANDARD))
+}
+
+/// Decodes output of `encode_base64`.
+pub fn decode_base64<T>(codec: &Codec<T>, text: &str) -> Result<T, EvalError> {
+    codec.decode(&try!(text.from_base64()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_base64, encode_base64};
+    use codec::{self, Codec};
+    use {make_sample_data, Document, EvalError};
+
+    #[test]
+    fn round_trips() {
+        let document = make_sample_data(1024);
+        let text = encode_base64(&codec::SerdeAndBincode, &document).unwrap();
+        assert_eq!(decode_base64::<Document>(&codec::SerdeAndBincode, &text).unwrap(), document);
+
+        // Bincode outputs of 8, 9 and 10 bytes need every amount of padding.
+        let codec: &Codec<Vec<u8>> = &codec::SerdeAndBincode;
+        for (len, padding) in (0..3).zip(&[1, 0, 2]) {
+            let bytes = vec![7u8; len];
+            let text = encode_base64(codec, &bytes).unwrap();
+            assert_eq!(text.len() % 4, 0);
+            assert_eq!(text.bytes().rev().take_while(|&byte| byte == b'=').count(), *padding);
+            assert_eq!(decode_base64(codec, &text).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn invalid_base64() {
+        assert_err!(decode_base64::<Document>(&codec::SerdeAndBincode, "not base64!"),
+                    EvalError::Base64(_));
+    }
+
+    #[test]
+    fn sizes() {
+        println!("");
+        println!("Size of the big document before and after base64:");
+        let document = make_sample_data(1024 * 1024);
+        for codec in codec::all::<Document>() {
+            let binary = codec.encode(&document).unwrap().len();
+            let text = encode_base64(codec, &document).unwrap().len();
+            assert_eq!(text, (binary + 2) / 3 * 4);
+            println!("    {}: {} bytes, base64: {} bytes ({:+.1}%)",
+                     codec.name(),
+                     binary,
+                     text,
+                     (text as f64 / binary as f64 - 1.0) * 100.0);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use codec::Codec;
+use test::Bencher;
+
+// Generates an encode and a decode bench for each codec of each value.  The bench names have to be
+// spelled out since a macro can't build new identifiers.
+macro_rules! bench_all {
+    ($($codec:expr => { $($value:expr => $encode:ident, $decode:ident;)+ })+) => {
+        $($(
+            #[bench]
+            fn $encode(bencher: &mut ::test::Bencher) {
+                ::bench::bench_encode(bencher, &$codec, $value);
+            }
+
+            #[bench]
+            fn $decode(bencher: &mut ::test::Bencher) {
+                ::bench::bench_decode(bencher, &$codec, $value);
+            }
+        )+)+
+    }
+}
+
+// Asserts that `$expr` evaluates to an `Err` matching `$pattern`, then evaluates `$check` (if
+// given) with the pattern's bindings in scope.  Panic messages are prefixed with `$context`, which
+// defaults to the expression itself.
+macro_rules! assert_err {
+    ($expr:expr, $pattern:pat) => {
+        assert_err!($expr, $pattern => (), stringify!($expr))
+    };
+    ($expr:expr, $pattern:pat => $check:expr) => {
+        assert_err!($expr, $pattern => $check, stringify!($expr))
+    };
+    ($expr:expr, $pattern:pat, $context:expr) => {
+        assert_err!($expr, $pattern => (), $context)
+    };
+    ($expr:expr, $pattern:pat => $check:expr, $context:expr) => {
+        match $expr {
+            Err($pattern) => $check,
+            Err(error) => panic!("{}: unexpected error: {}", $context, error),
+            Ok(_) => panic!("{}: expected an error", $context),
+        }
+    };
+}
+
+pub fn bench_encode<T, C: Codec<T>>(bencher: &mut Bencher, codec: &C, value: T) {
+    bencher.iter(|| codec.encode(&value).unwrap())
+}
+
+pub fn bench_decode<T, C: Codec<T>>(bencher: &mut Bencher, codec: &C, value: T) {
+    let bytes = codec.encode(&value).unwrap();
+    bencher.iter(|| codec.decode(&bytes).unwrap())
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::str;
+use byteorder::{BigEndian, ByteOrder};
+use EvalError;
+
+/// Map whose keys borrow from the buffer it was decoded from.
+#[derive(Debug, PartialEq)]
+pub struct BorrowMap<'a> {
+    pub m: HashMap<&'a str, u64>,
+}
+
+/// Reads a big-endian u64, advancing `bytes` past it.
+pub fn read_u64(bytes: &mut &[u8]) -> Result<u64, EvalError> {
+    if bytes.len() < 8 {
+        return Err(EvalError::TruncatedInput);
+    }
+    let value = BigEndian::read_u64(bytes);
+    *bytes = &bytes[8..];
+    Ok(value)
+}
+
+/// Reads a bincode collection length, returning it with a capacity to reserve for that many
+/// elements of at least `min_element_size` bytes each.  Don't trust the length for the
+/// allocation: the capacity is capped at what the rest of `bytes` could hold.
+pub fn read_capacity(bytes: &mut &[u8],
+                     min_element_size: u64)
+                     -> Result<(u64, usize), EvalError> {
+    let len = try!(read_u64(bytes));
+    let capacity = ::std::cmp::min(len, bytes.len() as u64 / min_element_size);
+    Ok((len, capacity as usize))
+}
+
+/// Reads a bincode byte vector, borrowing it from `bytes`.
+pub fn read_bytes<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], EvalError> {
+    let len = try!(read_u64(bytes));
+    if (bytes.len() as u64) < len {
+        return Err(EvalError::TruncatedInput);
+    }
+    let (value, rest) = bytes.split_at(len as usize);
+    *bytes = rest;
+    Ok(value)
+}
+
+/// Reads a bincode string, borrowing it from `bytes`.
+pub fn read_str<'a>(bytes: &mut &'a [u8]) -> Result<&'a str, EvalError> {
+    Ok(try!(str::from_utf8(try!(read_bytes(bytes)))))
+}
+
+/// Decodes a bincode-encoded `HashMap<String, u64>` without copying its keys.  serde 0.6 can only
+/// deserialize owned values, so this reads bincode's layout by hand: a u64 entry count, then
+/// each key as a u64 length and its UTF-8 bytes, followed by its value.
+pub fn decode_borrowed(mut bytes: &[u8]) -> Result<BorrowMap, EvalError> {
+    // Each entry takes at least 16 bytes: its key's length and its value.
+    let (len, capacity) = try!(read_capacity(&mut bytes, 16));
+    let mut m = HashMap::with_capacity(capacity);
+    for _ in 0..len {
+        let key = try!(read_str(&mut bytes));
+        let value = try!(read_u64(&mut bytes));
+        m.insert(key, value);
+    }
+    Ok(BorrowMap { m: m })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::decode_borrowed;
+    use test::Bencher;
+    use EvalError;
+
+    fn make_map(count: u64) -> HashMap<String, u64> {
+        (0..count).map(|i| (format!("key number {}", i), i)).collect()
+    }
+
+    #[test]
+    fn matches_owned() {
+        let owned = make_map(1000);
+        let bytes = ::serde_and_bincode::encode(&owned);
+        let borrowed = decode_borrowed(&bytes).unwrap();
+        assert_eq!(borrowed.m.len(), owned.len());
+        let range = bytes.as_ptr() as usize..bytes.as_ptr() as usize + bytes.len();
+        for (key, value) in &borrowed.m {
+            assert_eq!(owned[*key], *value);
+            let address = key.as_ptr() as usize;
+            assert!(address >= range.start && address < range.end);
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = ::serde_and_bincode::encode(&make_map(10));
+        assert_err!(decode_borrowed(&bytes[..bytes.len() - 1]), EvalError::TruncatedInput);
+    }
+
+    #[bench]
+    fn serde_and_bincode_decode_map_owned_keys(bencher: &mut Bencher) {
+        let bytes = ::serde_and_bincode::encode(&make_map(10_000));
+        bencher.iter(|| ::serde_and_bincode::decode::<HashMap<String, u64>>(&bytes))
+    }
+
+    #[bench]
+    fn bincode_decode_map_borrowed_keys(bencher: &mut Bencher) {
+        let bytes = ::serde_and_bincode::encode(&make_map(10_000));
+        bencher.iter(|| decode_borrowed(&bytes).unwrap().m.len())
+    }
+}
@@ -0,0 +1,124 @@
+use rustc_serialize::{Decodable, Decoder, Encodable, Encoder};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::bytes::ByteBuf;
+use {Document, Person};
+
+/// Content which is encoded as a single byte string rather than as a sequence of integers.  For
+/// rustc-serialize this relies on the CBOR encoder special-casing structs named `CborBytes`.
+#[derive(Debug, PartialEq)]
+pub struct ByteContent(pub Vec<u8>);
+
+impl Serialize for ByteContent {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        serializer.visit_bytes(&self.0)
+    }
+}
+
+impl Deserialize for ByteContent {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<ByteContent, D::Error> {
+        ByteBuf::deserialize(deserializer).map(|buf| ByteContent(buf.into()))
+    }
+}
+
+impl Encodable for ByteContent {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), E::Error> {
+        encoder.emit_struct("CborBytes", 1, |encoder| {
+            encoder.emit_struct_field("_field0", 0, |encoder| self.0.encode(encoder))
+        })
+    }
+}
+
+impl Decodable for ByteContent {
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<ByteContent, D::Error> {
+        Vec::decode(decoder).map(ByteContent)
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct DocumentBytes {
+    id: u64,
+    name: String,
+    authors: Vec<Person>,
+    content: ByteContent,
+}
+
+impl From<Document> for DocumentBytes {
+    fn from(document: Document) -> DocumentBytes {
+        DocumentBytes {
+            id: document.id,
+            name: document.name,
+            authors: document.authors,
+            content: ByteContent(document.content),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cbor::Encoder;
+    use super::DocumentBytes;
+    use make_sample_data;
+    use test::Bencher;
+
+    #[test]
+    fn sizes() {
+        println!("");
+        println!("Size after serialization with content as a byte string:");
+        let plain = make_sample_data(1024 * 1024);
+        let document = DocumentBytes::from(make_sample_data(1024 * 1024));
+
+        let cbor_plain = ::rustc_and_cbor::encode(&plain).len();
+        let cbor_bytes = ::rustc_and_cbor::encode(&document).len();
+        assert!(cbor_bytes < cbor_plain);
+        println!("Using CBOR format with rustc-serialize");
+        println!("    Vec<u8>: {} bytes", cbor_plain);
+        println!("    bytes:   {} bytes", cbor_bytes);
+
+        // Bincode writes a length prefix then the raw bytes either way.
+        let bincode_plain = ::serde_and_bincode::encode(&plain).len();
+        let bincode_bytes = ::serde_and_bincode::encode(&document).len();
+        assert_eq!(bincode_bytes, bincode_plain);
+        assert_eq!(::rustc_and_bincode::encode(&document).len(), bincode_plain);
+        println!("Using Bincode format with serde");
+        println!("    Vec<u8>: {} bytes", bincode_plain);
+        println!("    bytes:   {} bytes", bincode_bytes);
+    }
+
+    // The cbor crate has no packed mode: the only choice its encoder offers is where to write, and
+    // that doesn't change the output.  Encoding content as a byte string is the one saving left.
+    #[test]
+    fn cbor_has_no_packed_mode() {
+        let document = make_sample_data(1024 * 1024);
+        let from_memory = ::rustc_and_cbor::encode(&document);
+        let mut from_writer = Vec::new();
+        {
+            let mut encoder = Encoder::from_writer(&mut from_writer);
+            encoder.encode(&[&document]).unwrap();
+            encoder.flush().unwrap();
+        }
+        assert_eq!(from_writer, from_memory);
+    }
+
+    #[test]
+    fn round_trips() {
+        let document = DocumentBytes::from(make_sample_data(1024));
+        let cbor = ::rustc_and_cbor::encode(&document);
+        assert_eq!(::rustc_and_cbor::decode::<DocumentBytes>(&cbor), document);
+        let bincode = ::serde_and_bincode::encode(&document);
+        assert_eq!(::serde_and_bincode::decode::<DocumentBytes>(&bincode), document);
+        let bincode = ::rustc_and_bincode::encode(&document);
+        assert_eq!(::rustc_and_bincode::decode::<DocumentBytes>(&bincode), document);
+    }
+
+    #[bench]
+    fn rustc_and_cbor_encode_big_byte_content(bencher: &mut Bencher) {
+        let document = DocumentBytes::from(make_sample_data(1024 * 1024));
+        bencher.iter(|| ::rustc_and_cbor::encode(&document))
+    }
+
+    #[bench]
+    fn serde_and_bincode_encode_big_byte_content(bencher: &mut Bencher) {
+        let document = DocumentBytes::from(make_sample_data(1024 * 1024));
+        bencher.iter(|| ::serde_and_bincode::encode(&document))
+    }
+}
@@ -0,0 +1,91 @@
+use bytes::{Buf, ByteStr, Bytes};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use {Document, Person};
+
+/// Content held in a `bytes::Bytes`, encoded exactly like `Vec<u8>`.
+///
+/// Decoding can't borrow from the input, and `Bytes` can't take ownership of a `Vec`, so the
+/// decoded content is copied once more than for `Vec<u8>`.
+#[derive(Debug, PartialEq)]
+pub struct BytesContent(pub Bytes);
+
+impl Serialize for BytesContent {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        self.0.buf().bytes().serialize(serializer)
+    }
+}
+
+impl Deserialize for BytesContent {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<BytesContent, D::Error> {
+        let content: Vec<u8> = try!(Deserialize::deserialize(deserializer));
+        Ok(BytesContent(Bytes::from_slice(&content)))
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct DocumentBytesCrate {
+    id: u64,
+    name: String,
+    authors: Vec<Person>,
+    content: BytesContent,
+}
+
+impl From<Document> for DocumentBytesCrate {
+    fn from(document: Document) -> DocumentBytesCrate {
+        DocumentBytesCrate {
+            id: document.id,
+            name: document.name,
+            authors: document.authors,
+            content: BytesContent(Bytes::from_slice(&document.content)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DocumentBytesCrate;
+    use {make_sample_data, Document};
+    use test::Bencher;
+
+    #[test]
+    fn same_encoding_as_vec() {
+        let document = make_sample_data(1024 * 1024);
+        let bytes = ::serde_and_bincode::encode(&document);
+        let decoded = ::serde_and_bincode::decode::<DocumentBytesCrate>(&bytes);
+        assert_eq!(::serde_and_bincode::encode(&decoded), bytes);
+        assert_eq!(decoded, DocumentBytesCrate::from(document));
+    }
+
+    #[cfg(feature = "counting_alloc")]
+    #[test]
+    fn decode_allocations() {
+        use counting_allocator::allocations;
+        println!("");
+        println!("Allocations while decoding the big document:");
+        let bytes = ::serde_and_bincode::encode(&make_sample_data(1024 * 1024));
+
+        let before = allocations();
+        ::serde_and_bincode::decode::<Document>(&bytes);
+        let vec_allocations = allocations() - before;
+
+        let before = allocations();
+        ::serde_and_bincode::decode::<DocumentBytesCrate>(&bytes);
+        let bytes_allocations = allocations() - before;
+
+        assert!(bytes_allocations > vec_allocations);
+        println!("    Vec<u8>: {}", vec_allocations);
+        println!("    Bytes:   {}", bytes_allocations);
+    }
+
+    #[bench]
+    fn serde_and_bincode_decode_big_vec_content(bencher: &mut Bencher) {
+        let bytes = ::serde_and_bincode::encode(&make_sample_data(1024 * 1024));
+        bencher.iter(|| ::serde_and_bincode::decode::<Document>(&bytes))
+    }
+
+    #[bench]
+    fn serde_and_bincode_decode_big_bytes_content(bencher: &mut Bencher) {
+        let bytes = ::serde_and_bincode::encode(&make_sample_data(1024 * 1024));
+        bencher.iter(|| ::serde_and_bincode::decode::<DocumentBytesCrate>(&bytes))
+    }
+}
@@ -0,0 +1,103 @@
+use rustc_serialize::{Decodable, Encodable, Encoder as RustcEncoder};
+use cbor::{Cbor, CborError, Decoder, Encoder};
+use EvalError;
+
+/// Wraps a CBOR item so that it's encoded deterministically as RFC 8949 section 4.2.1 describes:
+/// map keys sorted by their encoded bytes, at every depth.  The `cbor` crate's encoder already
+/// writes integers and lengths in their shortest form, so sorting is all that's missing.  Tagged
+/// items and floats are passed through as they are.
+struct Canonical<'a>(&'a Cbor);
+
+impl<'a> Encodable for Canonical<'a> {
+    fn encode<E: RustcEncoder>(&self, encoder: &mut E) -> Result<(), E::Error> {
+        match *self.0 {
+            Cbor::Array(ref items) => {
+                encoder.emit_seq(items.len(), |encoder| {
+                    for (index, item) in items.iter().enumerate() {
+                        try!(encoder.emit_seq_elt(index, |encoder| {
+                            Canonical(item).encode(encoder)
+                        }));
+                    }
+                    Ok(())
+                })
+            }
+            Cbor::Map(ref map) => {
+                // A text key's encoding starts with its length, so ordering by length and then by
+                // content is the same as ordering by the encoded bytes.
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by(|&(left, _), &(right, _)| {
+                    (left.len(), left).cmp(&(right.len(), right))
+                });
+                encoder.emit_map(entries.len(), |encoder| {
+                    for (index, &(key, value)) in entries.iter().enumerate() {
+                        try!(encoder.emit_map_elt_key(index, |encoder| key.encode(encoder)));
+                        try!(encoder.emit_map_elt_val(index, |encoder| {
+                            Canonical(value).encode(encoder)
+                        }));
+                    }
+                    Ok(())
+                })
+            }
+            ref item => item.encode(encoder),
+        }
+    }
+}
+
+/// Encodes `v` with rustc-serialize and CBOR, then re-encodes the result canonically.
+pub fn encode_result<T: Encodable>(v: &T) -> Result<Vec<u8>, EvalError> {
+    let bytes = try!(::rustc_and_cbor::encode_result(v));
+    let item = match Decoder::from_bytes(bytes).items().next() {
+        Some(result) => try!(result),
+        None => return Err(EvalError::Cbor(CborError::UnexpectedEOF)),
+    };
+    let mut encoder = Encoder::from_memory();
+    try!(encoder.encode(&[Canonical(&item)]));
+    Ok(encoder.as_bytes().to_vec())
+}
+
+/// Canonical CBOR is still CBOR, so it decodes the usual way.
+pub fn decode_result<T: Decodable>(bytes: &[u8]) -> Result<T, EvalError> {
+    ::rustc_and_cbor::decode_result(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::{decode_result, encode_result};
+    use {make_seeded_sample_data, Document};
+
+    #[test]
+    fn same_content_same_bytes() {
+        let document = make_seeded_sample_data(1024);
+        let mut content = Vec::new();
+        content.extend_from_slice(&document.content);
+        let rebuilt = Document {
+            id: document.id,
+            name: document.name.clone(),
+            authors: decode_result(&encode_result(&document.authors).unwrap()).unwrap(),
+            content: content,
+        };
+        assert_eq!(encode_result(&rebuilt).unwrap(), encode_result(&document).unwrap());
+
+        // Each `HashMap` iterates in an order of its own, but the canonical encoding doesn't
+        // depend on it.
+        let keys: Vec<_> = (0..100).map(|i| format!("key {}", i)).collect();
+        let forwards: HashMap<_, _> = keys.iter().cloned().zip(0..100).collect();
+        let backwards: HashMap<_, _> = keys.iter().cloned().zip(0..100).rev().collect();
+        assert_eq!(encode_result(&forwards).unwrap(), encode_result(&backwards).unwrap());
+    }
+
+    #[test]
+    fn sorted_keys() {
+        let mut map = HashMap::new();
+        map.insert("bb".to_string(), 2u8);
+        map.insert("a".to_string(), 1u8);
+        map.insert("ab".to_string(), 3u8);
+        map.insert("ccc".to_string(), 4u8);
+        let expected = [0xa4, 0x61, b'a', 1, 0x62, b'a', b'b', 3, 0x62, b'b', b'b', 2, 0x63, b'c',
+                        b'c', b'c', 4];
+        let bytes = encode_result(&map).unwrap();
+        assert_eq!(&bytes[..], &expected[..]);
+        assert_eq!(decode_result::<HashMap<String, u8>>(&bytes).unwrap(), map);
+    }
+}
@@ -0,0 +1,55 @@
+/// Characters from each UTF-8 width class: ASCII, a non-BMP emoji and a combining mark.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct CharRecord {
+    pub first: char,
+    pub emoji: char,
+    pub combining: char,
+}
+
+pub fn make_char_record() -> CharRecord {
+    CharRecord {
+        first: 'a',
+        emoji: '\u{1f600}',
+        combining: '\u{301}',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{make_char_record, CharRecord};
+
+    // bincode 0.4's serde serializer writes a char as a length-prefixed string, but its
+    // deserializer expects the bare UTF-8 bytes, so the first length byte is read as a char.
+    #[test]
+    fn round_trips() {
+        let expected = [("rustc_and_cbor", true),
+                        ("serde_and_bincode", false),
+                        ("rustc_and_bincode", true),
+                        ("serde_and_json", true),
+                        ("rustc_and_cbor_canonical", true)];
+        let codecs = ::codec::all::<CharRecord>();
+        assert_eq!(codecs.len(), expected.len());
+        let record = make_char_record();
+        for (codec, &(name, round_trips)) in codecs.iter().zip(expected.iter()) {
+            assert_eq!(codec.name(), name);
+            let bytes = codec.encode(&record).unwrap();
+            let decoded = codec.decode(&bytes);
+            assert!(decoded.ok().map_or(false, |decoded| decoded == record) == round_trips,
+                    "{} unexpectedly {} a record of chars",
+                    name,
+                    if round_trips { "mangled" } else { "round-tripped" });
+        }
+        let bytes = ::serde_and_bincode::encode(&record);
+        assert_eq!(bytes, ::serde_and_bincode::encode(&("a", "\u{1f600}", "\u{301}")));
+    }
+
+    #[test]
+    fn sizes() {
+        println!("");
+        println!("Size of a record of three chars:");
+        let record = make_char_record();
+        for codec in ::codec::all::<CharRecord>() {
+            println!("    {}: {} bytes", codec.name(), codec.encode(&record).unwrap().len());
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use {Document, Person};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Document whose content is held as a list of buffers rather than one.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct ChunkedDocument {
+    id: u64,
+    name: String,
+    authors: Vec<Person>,
+    content: Vec<Vec<u8>>,
+}
+
+impl From<Document> for ChunkedDocument {
+    fn from(document: Document) -> ChunkedDocument {
+        ChunkedDocument {
+            id: document.id,
+            name: document.name,
+            authors: document.authors,
+            content: document.content.chunks(CHUNK_SIZE).map(|chunk| chunk.to_vec()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChunkedDocument;
+    use codec;
+    use {make_sample_data, Document};
+
+    #[test]
+    fn sizes() {
+        println!("");
+        println!("Size of the big document with content in 16 chunks:");
+        let flat = make_sample_data(1024 * 1024);
+        let chunked = ChunkedDocument::from(make_sample_data(1024 * 1024));
+        assert_eq!(chunked.content.len(), 16);
+        let codecs = codec::all::<ChunkedDocument>().into_iter().zip(codec::all::<Document>());
+        for (chunked_codec, flat_codec) in codecs {
+            let chunked_bytes = chunked_codec.encode(&chunked).unwrap();
+            assert_eq!(chunked_codec.decode(&chunked_bytes).unwrap(), chunked);
+            println!("Using {}", chunked_codec.name());
+            println!("    flat:    {} bytes", flat_codec.encode(&flat).unwrap().len());
+            println!("    chunked: {} bytes", chunked_bytes.len());
+        }
+    }
+
+    fn chunked() -> ChunkedDocument {
+        ChunkedDocument::from(make_sample_data(1024 * 1024))
+    }
+
+    bench_all! {
+        codec::RustcAndCbor => {
+            chunked() => rustc_and_cbor_encode_big_chunked, rustc_and_cbor_decode_big_chunked;
+        }
+        codec::SerdeAndBincode => {
+            chunked() => serde_and_bincode_encode_big_chunked, serde_and_bincode_decode_big_chunked;
+        }
+        codec::RustcAndBincode => {
+            chunked() => rustc_and_bincode_encode_big_chunked, rustc_and_bincode_decode_big_chunked;
+        }
+        codec::SerdeAndJson => {
+            chunked() => serde_and_json_encode_big_chunked, serde_and_json_decode_big_chunked;
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use byteorder::{BigEndian, ByteOrder};
+use serde::{Deserialize, Serialize};
+use EvalError;
+
+const HEADER_SIZE: usize = 8;
+
+/// Encodes `v` with bincode and splits the result into chunks of at most `chunk_size` payload
+/// bytes.  Each chunk is prefixed by its big-endian `u32` index and the `u32` total chunk count.
+///
+/// Panics if `chunk_size` is zero, or if the output would need more chunks than a `u32` can count.
+pub fn encode_chunked<T: Serialize>(v: &T, chunk_size: usize) -> Vec<Vec<u8>> {
+    assert!(chunk_size > 0, "chunk size must be greater than zero");
+    let bytes = ::serde_and_bincode::encode(v);
+    let count = bytes.chunks(chunk_size).len();
+    assert!(count as u64 <= u32::max_value() as u64, "too many chunks for a u32 count");
+    bytes.chunks(chunk_size)
+         .enumerate()
+         .map(|(index, payload)| {
+             let mut chunk = vec![0; HEADER_SIZE];
+             BigEndian::write_u32(&mut chunk[..4], index as u32);
+             BigEndian::write_u32(&mut chunk[4..HEADER_SIZE], count as u32);
+             chunk.extend_from_slice(payload);
+             chunk
+         })
+         .collect()
+}
+
+/// Reassembles chunks produced by `encode_chunked`, which must be passed in order.
+pub fn decode_chunked<T: Deserialize>(chunks: &[Vec<u8>]) -> Result<T, EvalError> {
+    // Past this, the indices and counts below would be truncated.
+    if chunks.len() as u64 > u32::max_value() as u64 {
+        return Err(EvalError::TooManyChunks(chunks.len() as u64));
+    }
+    let mut bytes = Vec::new();
+    for (expected, chunk) in chunks.iter().enumerate() {
+        if chunk.len() < HEADER_SIZE {
+            return Err(EvalError::TruncatedChunk);
+        }
+        let index = BigEndian::read_u32(&chunk[..4]);
+        let count = BigEndian::read_u32(&chunk[4..HEADER_SIZE]);
+        if index != expected as u32 {
+            return Err(EvalError::ChunkOutOfOrder {
+                expected: expected as u32,
+                got: index,
+            });
+        }
+        if count != chunks.len() as u32 {
+            return Err(EvalError::ChunkCountMismatch {
+                expected: count,
+                got: chunks.len() as u32,
+            });
+        }
+        bytes.extend_from_slice(&chunk[HEADER_SIZE..]);
+    }
+    ::serde_and_bincode::decode_result(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_chunked, encode_chunked};
+    use {make_sample_data, Document, EvalError};
+
+    #[test]
+    fn round_trip() {
+        let document = make_sample_data(1024 * 1024);
+        let chunks = encode_chunked(&document, 64 * 1024);
+        assert_eq!(chunks.len(), 17);
+        assert_eq!(decode_chunked::<Document>(&chunks).unwrap(), document);
+    }
+
+    #[test]
+    fn out_of_order() {
+        let mut chunks = encode_chunked(&make_sample_data(1024 * 1024), 64 * 1024);
+        chunks.swap(3, 4);
+        assert_err!(decode_chunked::<Document>(&chunks),
+                    EvalError::ChunkOutOfOrder { expected: 3, got: 4 });
+    }
+
+    #[test]
+    fn missing_chunk() {
+        let mut chunks = encode_chunked(&make_sample_data(1024 * 1024), 64 * 1024);
+        chunks.pop();
+        assert_err!(decode_chunked::<Document>(&chunks),
+                    EvalError::ChunkCountMismatch { expected: 17, got: 16 });
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk size must be greater than zero")]
+    fn zero_chunk_size() {
+        encode_chunked(&make_sample_data(1024), 0);
+    }
+}
@@ -0,0 +1,293 @@
+use std::time::{Duration, Instant};
+use rustc_serialize::{Decodable, Encodable};
+use serde::{Deserialize, Serialize};
+use EvalError;
+
+/// A format and serialization library pairing which can encode and decode values of type `T`.
+pub trait Codec<T> {
+    /// Identifies the codec on the wire.
+    fn id(&self) -> u8;
+    fn name(&self) -> &'static str;
+    fn encode(&self, value: &T) -> Result<Vec<u8>, EvalError>;
+    fn decode(&self, bytes: &[u8]) -> Result<T, EvalError>;
+}
+
+pub struct RustcAndCbor;
+pub struct SerdeAndBincode;
+pub struct RustcAndBincode;
+pub struct SerdeAndJson;
+pub struct RustcAndCborCanonical;
+
+static RUSTC_AND_CBOR: RustcAndCbor = RustcAndCbor;
+static SERDE_AND_BINCODE: SerdeAndBincode = SerdeAndBincode;
+static RUSTC_AND_BINCODE: RustcAndBincode = RustcAndBincode;
+static SERDE_AND_JSON: SerdeAndJson = SerdeAndJson;
+static RUSTC_AND_CBOR_CANONICAL: RustcAndCborCanonical = RustcAndCborCanonical;
+
+impl<T: Encodable + Decodable> Codec<T> for RustcAndCbor {
+    fn id(&self) -> u8 {
+        0
+    }
+
+    fn name(&self) -> &'static str {
+        "rustc_and_cbor"
+    }
+
+    fn encode(&self, value: &T) -> Result<Vec<u8>, EvalError> {
+        ::rustc_and_cbor::encode_result(value)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<T, EvalError> {
+        ::rustc_and_cbor::decode_result(bytes)
+    }
+}
+
+impl<T: Serialize + Deserialize> Codec<T> for SerdeAndBincode {
+    fn id(&self) -> u8 {
+        1
+    }
+
+    fn name(&self) -> &'static str {
+        "serde_and_bincode"
+    }
+
+    fn encode(&self, value: &T) -> Result<Vec<u8>, EvalError> {
+        ::serde_and_bincode::encode_result(value)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<T, EvalError> {
+        ::serde_and_bincode::decode_result(bytes)
+    }
+}
+
+impl<T: Encodable + Decodable> Codec<T> for RustcAndBincode {
+    fn id(&self) -> u8 {
+        2
+    }
+
+    fn name(&self) -> &'static str {
+        "rustc_and_bincode"
+    }
+
+    fn encode(&self, value: &T) -> Result<Vec<u8>, EvalError> {
+        ::rustc_and_bincode::encode_result(value)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<T, EvalError> {
+        ::rustc_and_bincode::decode_result(bytes)
+    }
+}
+
+impl<T: Serialize + Deserialize> Codec<T> for SerdeAndJson {
+    fn id(&self) -> u8 {
+        3
+    }
+
+    fn name(&self) -> &'static str {
+        "serde_and_json"
+    }
+
+    fn encode(&self, value: &T) -> Result<Vec<u8>, EvalError> {
+        ::serde_and_json::encode_result(value)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<T, EvalError> {
+        ::serde_and_json::decode_result(bytes)
+    }
+}
+
+impl<T: Encodable + Decodable> Codec<T> for RustcAndCborCanonical {
+    fn id(&self) -> u8 {
+        4
+    }
+
+    fn name(&self) -> &'static str {
+        "rustc_and_cbor_canonical"
+    }
+
+    fn encode(&self, value: &T) -> Result<Vec<u8>, EvalError> {
+        ::cbor_canonical::encode_result(value)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<T, EvalError> {
+        ::cbor_canonical::decode_result(bytes)
+    }
+}
+
+/// Returns every codec in the suite.
+pub fn all<T>() -> Vec<&'static Codec<T>>
+    where T: Encodable + Decodable + Serialize + Deserialize
+{
+    vec![&RUSTC_AND_CBOR,
+         &SERDE_AND_BINCODE,
+         &RUSTC_AND_BINCODE,
+         &SERDE_AND_JSON,
+         &RUSTC_AND_CBOR_CANONICAL]
+}
+
+/// Returns the codec called `name`, if there is one.
+pub fn by_name<T>(name: &str) -> Option<&'static Codec<T>>
+    where T: Encodable + Decodable + Serialize + Deserialize
+{
+    all().into_iter().find(|codec| codec.name() == name)
+}
+
+/// Encodes `value` with `codec`, also returning how long that took.
+pub fn encode_timed<T>(codec: &Codec<T>, value: &T) -> Result<(Vec<u8>, Duration), EvalError> {
+    let start = Instant::now();
+    let bytes = try!(codec.encode(value));
+    Ok((bytes, start.elapsed()))
+}
+
+/// Decodes `bytes` with `codec`, also returning how long that took.
+pub fn decode_timed<T>(codec: &Codec<T>, bytes: &[u8]) -> Result<(T, Duration), EvalError> {
+    let start = Instant::now();
+    let value = try!(codec.decode(bytes));
+    Ok((value, start.elapsed()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+    use rand::{self, Rng};
+    use {make_people, make_sample_data, Document, Person};
+
+    #[test]
+    fn by_name() {
+        for codec in super::all::<Document>() {
+            assert_eq!(super::by_name::<Document>(codec.name()).unwrap().id(), codec.id());
+        }
+        assert!(super::by_name::<Document>("json").is_none());
+    }
+
+    #[test]
+    fn timed() {
+        let document = make_sample_data(1024 * 1024);
+        for codec in super::all::<Document>() {
+            let (bytes, encode_time) = super::encode_timed(codec, &document).unwrap();
+            assert_eq!(bytes, codec.encode(&document).unwrap());
+            assert!(encode_time > Duration::new(0, 0), "{} took no time", codec.name());
+            let (decoded, decode_time) = super::decode_timed(codec, &bytes).unwrap();
+            assert_eq!(decoded, document);
+            assert!(decode_time > Duration::new(0, 0), "{} took no time", codec.name());
+        }
+    }
+
+    #[test]
+    fn concurrent_decode() {
+        let document = Arc::new(make_sample_data(64 * 1024));
+        for codec in super::all::<Document>() {
+            let bytes = Arc::new(codec.encode(&*document).unwrap());
+            let name = codec.name();
+            let mut threads = Vec::new();
+            for _ in 0..16 {
+                let bytes = bytes.clone();
+                let document = document.clone();
+                threads.push(thread::spawn(move || {
+                    let codec = super::by_name::<Document>(name).unwrap();
+                    let decoded = codec.decode(&bytes).unwrap();
+                    let diffs = ::diff::compare_documents(&document, &decoded);
+                    assert!(diffs.is_empty(), "{}: {}", name, ::diff::describe(&diffs));
+                }));
+            }
+            for thread in threads {
+                thread.join().unwrap();
+            }
+        }
+    }
+
+    // Guards against decoders keeping state between calls, on the big document so that any
+    // content-dependent nondeterminism shows up.  Too slow for debug builds, so run with
+    // `cargo test --release decode_idempotent -- --ignored`.
+    #[test]
+    #[ignore]
+    fn decode_idempotent() {
+        let document = make_sample_data(1024 * 1024);
+        for codec in super::all::<Document>() {
+            let bytes = codec.encode(&document).unwrap();
+            for _ in 0..100 {
+                assert!(codec.decode(&bytes).unwrap() == document,
+                        "{} decoded differently on a later call",
+                        codec.name());
+            }
+        }
+    }
+
+    #[test]
+    fn decode_empty() {
+        for codec in super::all::<Document>() {
+            for bytes in &[&[][..], &[0][..]] {
+                assert!(codec.decode(bytes).is_err(),
+                        "{} decoded {:?} as a document",
+                        codec.name(),
+                        bytes);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_utf8_rejected() {
+        let person = make_people(1).pop().unwrap();
+        for codec in super::all::<Person>() {
+            let mut bytes = codec.encode(&person).unwrap();
+            let name = bytes.windows(person.name.len())
+                            .position(|window| window == person.name.as_bytes())
+                            .unwrap();
+            bytes[name] = 0xff;
+            assert!(codec.decode(&bytes).is_err(),
+                    "{} decoded a name which isn't UTF-8",
+                    codec.name());
+        }
+    }
+
+    // Only JSON checks that the whole input was consumed.  The others stop after the first value,
+    // so framing has to track message lengths itself.
+    #[test]
+    fn trailing_bytes_rejected() {
+        let expected = [("rustc_and_cbor", false),
+                        ("serde_and_bincode", false),
+                        ("rustc_and_bincode", false),
+                        ("serde_and_json", true),
+                        ("rustc_and_cbor_canonical", false)];
+        let document = make_sample_data(1024);
+        let codecs = super::all::<Document>();
+        assert_eq!(codecs.len(), expected.len());
+        for (codec, &(name, rejects)) in codecs.into_iter().zip(expected.iter()) {
+            assert_eq!(codec.name(), name);
+            let mut bytes = codec.encode(&document).unwrap();
+            bytes.push(0);
+            match codec.decode(&bytes) {
+                Ok(decoded) => {
+                    assert!(!rejects, "{} should reject trailing bytes", name);
+                    assert_eq!(decoded, document);
+                }
+                Err(error) => assert!(rejects, "{} failed to decode: {}", name, error),
+            }
+        }
+    }
+
+    // A longer content never encodes smaller than a prefix of it.  The shorter content has to be a
+    // prefix since CBOR and JSON spend more bytes on larger byte values.  Compressing the output
+    // would break this, but none of the codecs compress.
+    #[test]
+    fn size_monotonic_in_content_length() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            // Lengths up to 1024 cross CBOR's 256 boundary.
+            let (a, b) = (rng.gen_range(0, 1024), rng.gen_range(0, 1024));
+            let (shorter, longer) = if a < b { (a, b) } else { (b, a) };
+            let long = make_sample_data(longer);
+            let mut short = make_sample_data(0);
+            short.content = long.content[..shorter].to_vec();
+            for codec in super::all::<Document>() {
+                assert!(codec.encode(&short).unwrap().len() <= codec.encode(&long).unwrap().len(),
+                        "{} encoded {} content bytes larger than {}",
+                        codec.name(),
+                        shorter,
+                        longer);
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::time::{Duration, Instant};
+use test::black_box;
+
+// Comfortably larger than the last-level cache of the machines we run on.
+const SCRATCH_SIZE: usize = 64 * 1024 * 1024;
+const CACHE_LINE: usize = 64;
+
+/// Writes to every cache line of `scratch` so that previously touched data is evicted.
+fn evict_cache(scratch: &mut [u8]) {
+    let mut i = 0;
+    while i < scratch.len() {
+        scratch[i] = scratch[i].wrapping_add(1);
+        i += CACHE_LINE;
+    }
+    black_box(scratch);
+}
+
+/// Calls `f` `iterations` times and returns the mean time taken per call.  If `scratch` is given,
+/// the cache is evicted before each call, outside the timed section.
+pub fn mean_time<F: FnMut()>(iterations: u32,
+                             mut scratch: Option<&mut [u8]>,
+                             mut f: F)
+                             -> Duration {
+    let mut total = Duration::new(0, 0);
+    for _ in 0..iterations {
+        if let Some(ref mut scratch) = scratch {
+            evict_cache(scratch);
+        }
+        let start = Instant::now();
+        f();
+        total = total + start.elapsed();
+    }
+    total / iterations
+}
+
+pub fn micros(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000_000 + (duration.subsec_nanos() / 1000) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mean_time, micros, SCRATCH_SIZE};
+    use {make_sample_data, Document};
+    use test::black_box;
+
+    const ITERATIONS: u32 = 20;
+
+    // `Bencher::iter` can't leave the eviction out of the measurement, so this is timed manually.
+    // Run with `cargo test --release cold_cache_decode -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn cold_cache_decode() {
+        println!("");
+        println!("Mean time to decode the big document:");
+        let document = make_sample_data(1024 * 1024);
+        let mut scratch = vec![0u8; SCRATCH_SIZE];
+        for codec in ::codec::all::<Document>() {
+            let bytes = codec.encode(&document).unwrap();
+            let mut decode = || {
+                black_box(codec.decode(&bytes).unwrap());
+            };
+            let warm = mean_time(ITERATIONS, None, &mut decode);
+            let cold = mean_time(ITERATIONS, Some(&mut scratch), &mut decode);
+            println!("Using {}", codec.name());
+            println!("    warm cache: {} us", micros(warm));
+            println!("    cold cache: {} us", micros(cold));
+        }
+    }
+}
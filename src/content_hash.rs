@@ -0,0 +1,46 @@
+use sha256::sha256;
+use Document;
+
+/// SHA-256 of the document's canonical CBOR encoding, which depends only on its value.
+pub fn content_hash(document: &Document) -> [u8; 32] {
+    sha256(&::cbor_canonical::encode_result(document).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::content_hash;
+    use make_seeded_sample_data;
+
+    #[test]
+    fn equal_documents_hash_equally() {
+        let document = make_seeded_sample_data(1024);
+        let hash = content_hash(&document);
+        assert_eq!(content_hash(&make_seeded_sample_data(1024)), hash);
+        for codec in ::codec::all() {
+            let decoded = codec.decode(&codec.encode(&document).unwrap()).unwrap();
+            assert!(content_hash(&decoded) == hash, "{} changed the hash", codec.name());
+        }
+    }
+
+    #[test]
+    fn any_change_flips_hash() {
+        let document = make_seeded_sample_data(1024);
+        let hash = content_hash(&document);
+        let mut changes = Vec::new();
+        let mut changed = document.clone();
+        changed.id += 1;
+        changes.push(changed);
+        let mut changed = document.clone();
+        changed.name.push('!');
+        changes.push(changed);
+        let mut changed = document.clone();
+        changed.authors.swap(0, 1);
+        changes.push(changed);
+        let mut changed = document.clone();
+        changed.content[512] ^= 1;
+        changes.push(changed);
+        for changed in &changes {
+            assert!(content_hash(changed) != hash);
+        }
+    }
+}
@@ -0,0 +1,291 @@
+use std::str;
+use byteorder::{BigEndian, ByteOrder};
+use serde::de::{self, Deserialize, Deserializer, MapVisitor, SeqVisitor, Visitor};
+use serde_json;
+use EvalError;
+
+/// Any value, parsed and thrown away.
+struct Ignored;
+
+impl Deserialize for Ignored {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<Ignored, D::Error> {
+        deserializer.visit(IgnoredVisitor)
+    }
+}
+
+struct IgnoredVisitor;
+
+impl Visitor for IgnoredVisitor {
+    type Value = Ignored;
+
+    fn visit_bool<E: de::Error>(&mut self, _: bool) -> Result<Ignored, E> {
+        Ok(Ignored)
+    }
+
+    fn visit_i64<E: de::Error>(&mut self, _: i64) -> Result<Ignored, E> {
+        Ok(Ignored)
+    }
+
+    fn visit_u64<E: de::Error>(&mut self, _: u64) -> Result<Ignored, E> {
+        Ok(Ignored)
+    }
+
+    fn visit_f64<E: de::Error>(&mut self, _: f64) -> Result<Ignored, E> {
+        Ok(Ignored)
+    }
+
+    fn visit_str<E: de::Error>(&mut self, _: &str) -> Result<Ignored, E> {
+        Ok(Ignored)
+    }
+
+    fn visit_unit<E: de::Error>(&mut self) -> Result<Ignored, E> {
+        Ok(Ignored)
+    }
+
+    fn visit_none<E: de::Error>(&mut self) -> Result<Ignored, E> {
+        Ok(Ignored)
+    }
+
+    fn visit_seq<V: SeqVisitor>(&mut self, mut visitor: V) -> Result<Ignored, V::Error> {
+        while let Some(Ignored) = try!(visitor.visit()) {}
+        try!(visitor.end());
+        Ok(Ignored)
+    }
+
+    fn visit_map<V: MapVisitor>(&mut self, mut visitor: V) -> Result<Ignored, V::Error> {
+        while let Some((Ignored, Ignored)) = try!(visitor.visit()) {}
+        try!(visitor.end());
+        Ok(Ignored)
+    }
+}
+
+/// The number of elements in a sequence, which are parsed but not kept.
+struct SeqLen(u64);
+
+impl Deserialize for SeqLen {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<SeqLen, D::Error> {
+        deserializer.visit_seq(SeqLenVisitor)
+    }
+}
+
+struct SeqLenVisitor;
+
+impl Visitor for SeqLenVisitor {
+    type Value = SeqLen;
+
+    fn visit_seq<V: SeqVisitor>(&mut self, mut visitor: V) -> Result<SeqLen, V::Error> {
+        let mut len = 0;
+        while let Some(Ignored) = try!(visitor.visit()) {
+            len += 1;
+        }
+        try!(visitor.end());
+        Ok(SeqLen(len))
+    }
+}
+
+/// A map key, compared against "content" without being copied.
+struct ContentKey(bool);
+
+impl Deserialize for ContentKey {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<ContentKey, D::Error> {
+        deserializer.visit(ContentKeyVisitor)
+    }
+}
+
+struct ContentKeyVisitor;
+
+impl Visitor for ContentKeyVisitor {
+    type Value = ContentKey;
+
+    fn visit_str<E: de::Error>(&mut self, key: &str) -> Result<ContentKey, E> {
+        Ok(ContentKey(key == "content"))
+    }
+}
+
+/// The length of a document's content, if it has one.
+struct ContentLen(Option<u64>);
+
+impl Deserialize for ContentLen {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<ContentLen, D::Error> {
+        deserializer.visit_map(ContentLenVisitor)
+    }
+}
+
+struct ContentLenVisitor;
+
+impl Visitor for ContentLenVisitor {
+    type Value = ContentLen;
+
+    fn visit_map<V: MapVisitor>(&mut self, mut visitor: V) -> Result<ContentLen, V::Error> {
+        let mut len = None;
+        while let Some(ContentKey(is_content)) = try!(visitor.visit_key()) {
+            if is_content {
+                len = Some(try!(visitor.visit_value::<SeqLen>()).0);
+            } else {
+                try!(visitor.visit_value::<Ignored>());
+            }
+        }
+        try!(visitor.end());
+        Ok(ContentLen(len))
+    }
+}
+
+/// Reads the length of a JSON-encoded document's content without keeping any of it.  JSON gives
+/// no lengths up front, so every byte still has to be parsed, but no key or value is copied out.
+pub fn skip_to_content_len_json(bytes: &[u8]) -> Result<Option<u64>, EvalError> {
+    let text = try!(str::from_utf8(bytes));
+    Ok(try!(serde_json::from_str::<ContentLen>(text)).0)
+}
+
+/// Reads the major type and argument of the CBOR item at the front of `bytes`.
+fn read_header(bytes: &mut &[u8]) -> Result<(u8, u64), EvalError> {
+    let initial = match bytes.first() {
+        Some(&initial) => initial,
+        None => return Err(EvalError::TruncatedInput),
+    };
+    let size = match initial & 0x1f {
+        info @ 0...23 => {
+            *bytes = &bytes[1..];
+            return Ok((initial >> 5, info as u64));
+        }
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        // Indefinite lengths, which the encoder never writes, and reserved values.
+        _ => return Err(EvalError::UnexpectedCbor(initial)),
+    };
+    if bytes.len() < 1 + size {
+        return Err(EvalError::TruncatedInput);
+    }
+    let argument = BigEndian::read_uint(&bytes[1..], size);
+    *bytes = &bytes[1 + size..];
+    Ok((initial >> 5, argument))
+}
+
+/// Reads the argument of the CBOR item at the front of `bytes`, which must have major type `major`.
+fn expect_header(bytes: &mut &[u8], major: u8) -> Result<u64, EvalError> {
+    let initial = bytes.first().cloned();
+    match try!(read_header(bytes)) {
+        (actual, argument) if actual == major => Ok(argument),
+        _ => Err(EvalError::UnexpectedCbor(initial.unwrap_or(0))),
+    }
+}
+
+// Far deeper than any document, but shallow enough not to overflow the stack.
+const MAX_DEPTH: u32 = 128;
+
+fn skip_bytes(bytes: &mut &[u8], len: u64) -> Result<(), EvalError> {
+    if (bytes.len() as u64) < len {
+        return Err(EvalError::TruncatedInput);
+    }
+    *bytes = &bytes[len as usize..];
+    Ok(())
+}
+
+/// Skips the CBOR item at the front of `bytes`, reading only its headers.  Fails rather than
+/// recursing past `MAX_DEPTH` nested arrays, maps and tags.
+fn skip_item(bytes: &mut &[u8], depth: u32) -> Result<(), EvalError> {
+    if depth > MAX_DEPTH {
+        return Err(EvalError::TooDeeplyNested);
+    }
+    let (major, argument) = try!(read_header(bytes));
+    match major {
+        // Strings.
+        2 | 3 => skip_bytes(bytes, argument),
+        // Arrays.
+        4 => {
+            for _ in 0..argument {
+                try!(skip_item(bytes, depth + 1));
+            }
+            Ok(())
+        }
+        // Maps.
+        5 => {
+            for _ in 0..argument {
+                try!(skip_item(bytes, depth + 1));
+                try!(skip_item(bytes, depth + 1));
+            }
+            Ok(())
+        }
+        // Tags.
+        6 => skip_item(bytes, depth + 1),
+        // Integers, simple values and floats, all held in the argument.
+        _ => Ok(()),
+    }
+}
+
+/// Reads the length of a CBOR-encoded document's content from its array header.  Every other
+/// field is skipped by its length, so this never looks at the content's elements.
+pub fn skip_to_content_len_cbor(mut bytes: &[u8]) -> Result<Option<u64>, EvalError> {
+    let fields = try!(expect_header(&mut bytes, 5));
+    for _ in 0..fields {
+        let key_len = try!(expect_header(&mut bytes, 3));
+        let key = &bytes[..::std::cmp::min(key_len, bytes.len() as u64) as usize];
+        try!(skip_bytes(&mut bytes, key_len));
+        if key == b"content" {
+            return Ok(Some(try!(expect_header(&mut bytes, 4))));
+        }
+        try!(skip_item(&mut bytes, 0));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{skip_to_content_len_cbor, skip_to_content_len_json};
+    use test::Bencher;
+    use {make_people, make_sample_data, EvalError};
+
+    #[test]
+    fn finds_content_len() {
+        for &size in &[0, 23, 24, 1024, 1024 * 1024] {
+            let document = make_sample_data(size);
+            let json = ::serde_and_json::encode(&document);
+            assert_eq!(skip_to_content_len_json(&json).unwrap(), Some(size as u64));
+            let cbor = ::rustc_and_cbor::encode(&document);
+            assert_eq!(skip_to_content_len_cbor(&cbor).unwrap(), Some(size as u64));
+        }
+    }
+
+    #[test]
+    fn no_content() {
+        let person = make_people(1).pop().unwrap();
+        assert_eq!(skip_to_content_len_json(&::serde_and_json::encode(&person)).unwrap(), None);
+        assert_eq!(skip_to_content_len_cbor(&::rustc_and_cbor::encode(&person)).unwrap(), None);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let cbor = ::rustc_and_cbor::encode(&make_sample_data(1024));
+        // Cut off part way through the name.
+        assert_err!(skip_to_content_len_cbor(&cbor[..20]), EvalError::TruncatedInput);
+        assert_err!(skip_to_content_len_cbor(&::rustc_and_cbor::encode(&make_people(2))),
+                    EvalError::UnexpectedCbor(0x82));
+        assert!(skip_to_content_len_json(b"[1, 2]").is_err());
+    }
+
+    #[test]
+    fn too_deeply_nested() {
+        // A map whose first value is a million nested one-element arrays.
+        let mut cbor = vec![0xa1, 0x64];
+        cbor.extend_from_slice(b"name");
+        cbor.extend(vec![0x81; 1_000_000]);
+        cbor.push(0);
+        assert_err!(skip_to_content_len_cbor(&cbor), EvalError::TooDeeplyNested);
+    }
+
+    // Compare against `serde_and_json_decode_big`, which decodes the whole document.
+    #[bench]
+    fn serde_and_json_skip_to_content_len_big(bencher: &mut Bencher) {
+        let bytes = ::serde_and_json::encode(&make_sample_data(1024 * 1024));
+        bencher.iter(|| skip_to_content_len_json(&bytes).unwrap())
+    }
+
+    // Compare against `rustc_and_cbor_decode_big`.
+    #[bench]
+    fn cbor_skip_to_content_len_big(bencher: &mut Bencher) {
+        let bytes = ::rustc_and_cbor::encode(&make_sample_data(1024 * 1024));
+        bencher.iter(|| skip_to_content_len_cbor(&bytes).unwrap())
+    }
+}
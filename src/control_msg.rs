@@ -0,0 +1,117 @@
+/// A tiny RPC-style message, for measuring each codec's fixed cost per call.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct ControlMsg {
+    pub seq: u32,
+    pub kind: u8,
+    pub ack: bool,
+}
+
+pub fn make_control_msg(seq: u32) -> ControlMsg {
+    ControlMsg {
+        seq: seq,
+        kind: 3,
+        ack: seq % 2 == 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{make_control_msg, ControlMsg};
+    use bincode;
+    use cbor::Encoder;
+    use codec::{self, Codec};
+    use serde_json;
+    use test::{black_box, Bencher};
+
+    #[test]
+    fn sizes() {
+        println!("");
+        println!("Size of a control message:");
+        let message = make_control_msg(1000);
+        for codec in codec::all::<ControlMsg>() {
+            let bytes = codec.encode(&message).unwrap();
+            assert_eq!(codec.decode(&bytes).unwrap(), message);
+            println!("    {}: {} bytes", codec.name(), bytes.len());
+        }
+    }
+
+    // Each iteration encodes and decodes one message, so the result is the time per message.
+    fn bench_round_trip<C: Codec<ControlMsg>>(bencher: &mut Bencher, codec: &C) {
+        let message = make_control_msg(1000);
+        bencher.iter(|| {
+            let bytes = codec.encode(black_box(&message)).unwrap();
+            codec.decode(&bytes).unwrap()
+        })
+    }
+
+    #[bench]
+    fn rustc_and_cbor_round_trip_control_msg(bencher: &mut Bencher) {
+        bench_round_trip(bencher, &codec::RustcAndCbor);
+    }
+
+    #[bench]
+    fn serde_and_bincode_round_trip_control_msg(bencher: &mut Bencher) {
+        bench_round_trip(bencher, &codec::SerdeAndBincode);
+    }
+
+    #[bench]
+    fn rustc_and_bincode_round_trip_control_msg(bencher: &mut Bencher) {
+        bench_round_trip(bencher, &codec::RustcAndBincode);
+    }
+
+    #[bench]
+    fn serde_and_json_round_trip_control_msg(bencher: &mut Bencher) {
+        bench_round_trip(bencher, &codec::SerdeAndJson);
+    }
+
+    // serde_and_bincode's encode bench is `serde_and_bincode_encode_control_msg_vec` in lib.rs,
+    // next to the SmallVec variant.
+    fn bench_encode<C: Codec<ControlMsg>>(bencher: &mut Bencher, codec: &C) {
+        let message = make_control_msg(1000);
+        bencher.iter(|| codec.encode(black_box(&message)).unwrap())
+    }
+
+    #[bench]
+    fn rustc_and_cbor_encode_control_msg(bencher: &mut Bencher) {
+        bench_encode(bencher, &codec::RustcAndCbor);
+    }
+
+    #[bench]
+    fn rustc_and_bincode_encode_control_msg(bencher: &mut Bencher) {
+        bench_encode(bencher, &codec::RustcAndBincode);
+    }
+
+    #[bench]
+    fn serde_and_json_encode_control_msg(bencher: &mut Bencher) {
+        bench_encode(bencher, &codec::SerdeAndJson);
+    }
+
+    // Each of these builds and drops what the matching encode bench starts with, writing nothing,
+    // so the difference from the encode bench is the cost of the serialization itself.  The CBOR
+    // encoder preallocates a 64 KiB buffer, and serde_json's `to_vec` one of 128 bytes.
+    #[bench]
+    fn rustc_and_cbor_construct_encoder(bencher: &mut Bencher) {
+        bencher.iter(|| Encoder::from_memory())
+    }
+
+    #[bench]
+    fn serde_and_bincode_construct_serializer(bencher: &mut Bencher) {
+        bencher.iter(|| {
+            let mut bytes = Vec::new();
+            black_box(bincode::serde::Serializer::new(&mut bytes));
+        })
+    }
+
+    #[bench]
+    fn rustc_and_bincode_construct_encoder(bencher: &mut Bencher) {
+        bencher.iter(|| {
+            let mut bytes = Vec::new();
+            black_box(bincode::rustc_serialize::EncoderWriter::new(&mut bytes));
+        })
+    }
+
+    #[bench]
+    fn serde_and_json_construct_serializer(bencher: &mut Bencher) {
+        bencher.iter(|| serde_json::Serializer::new(Vec::with_capacity(128)))
+    }
+}
@@ -0,0 +1,33 @@
+/// Computes the CRC-32 (IEEE 802.3, as used by zlib and PNG) of `bytes`.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    crc32_concat(&[bytes])
+}
+
+/// Computes the CRC-32 of `parts` joined end to end, without copying them together.
+pub fn crc32_concat(parts: &[&[u8]]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in parts.iter().flat_map(|part| part.iter()) {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crc32, crc32_concat};
+
+    #[test]
+    fn known_checksums() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(crc32(b"The quick brown fox jumps over the lazy dog"), 0x414fa339);
+        assert_eq!(crc32_concat(&[&b"1234"[..], &b""[..], &b"56789"[..]]), 0xcbf43926);
+    }
+}
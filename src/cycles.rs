@@ -0,0 +1,58 @@
+/// Reads the CPU's timestamp counter.  On recent x86_64 parts this ticks at a constant rate
+/// regardless of frequency scaling, so counts are comparable across machines only roughly.
+pub fn rdtsc() -> u64 {
+    let low: u32;
+    let high: u32;
+    unsafe {
+        asm!("rdtsc" : "={eax}"(low), "={edx}"(high) : : : "volatile");
+    }
+    (high as u64) << 32 | low as u64
+}
+
+/// Calls `f` `iterations` times and returns the mean number of cycles taken per call.
+pub fn mean_cycles<F: FnMut()>(iterations: u32, mut f: F) -> u64 {
+    let mut total = 0;
+    for _ in 0..iterations {
+        let start = rdtsc();
+        f();
+        total += rdtsc() - start;
+    }
+    total / iterations as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mean_cycles, rdtsc};
+    use {make_sample_data, Document};
+    use test::black_box;
+
+    const ITERATIONS: u32 = 20;
+
+    #[test]
+    fn counts_up() {
+        let first = rdtsc();
+        assert!(rdtsc() > first);
+    }
+
+    // Run with `cargo test --release --features cycles cycles_per_byte -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn cycles_per_byte() {
+        println!("");
+        println!("Mean cycles per encoded byte for the big document:");
+        let document = make_sample_data(1024 * 1024);
+        for codec in ::codec::all::<Document>() {
+            let bytes = codec.encode(&document).unwrap();
+            let encode = mean_cycles(ITERATIONS, || {
+                black_box(codec.encode(&document).unwrap());
+            });
+            let decode = mean_cycles(ITERATIONS, || {
+                black_box(codec.decode(&bytes).unwrap());
+            });
+            println!("Using {}", codec.name());
+            println!("    encode: {:.2}, decode: {:.2}",
+                     encode as f64 / bytes.len() as f64,
+                     decode as f64 / bytes.len() as f64);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use {Document, EvalError, Person};
+
+#[derive(Serialize)]
+struct DedupedRef<'a> {
+    id: u64,
+    name: &'a str,
+    people: Vec<&'a Person>,
+    authors: Vec<u32>,
+    content: &'a [u8],
+}
+
+#[derive(Deserialize)]
+struct Deduped {
+    id: u64,
+    name: String,
+    people: Vec<Person>,
+    authors: Vec<u32>,
+    content: Vec<u8>,
+}
+
+/// Encodes `document` with bincode, writing each distinct author once in a table and the author
+/// list as indices into that table.
+pub fn encode_deduped(document: &Document) -> Vec<u8> {
+    let mut people = Vec::new();
+    let mut indices = HashMap::new();
+    let authors = document.authors
+                          .iter()
+                          .map(|person| {
+                              let key = (person.id, &person.name, &person.email);
+                              *indices.entry(key).or_insert_with(|| {
+                                  people.push(person);
+                                  people.len() as u32 - 1
+                              })
+                          })
+                          .collect();
+    ::serde_and_bincode::encode(&DedupedRef {
+        id: document.id,
+        name: &document.name,
+        people: people,
+        authors: authors,
+        content: &document.content,
+    })
+}
+
+/// Decodes output of `encode_deduped`, giving each author slot its own copy of the person.
+pub fn decode_deduped(bytes: &[u8]) -> Result<Document, EvalError> {
+    let deduped: Deduped = try!(::serde_and_bincode::decode_result(bytes));
+    let mut authors = Vec::with_capacity(deduped.authors.len());
+    for index in deduped.authors {
+        let person = match deduped.people.get(index as usize) {
+            Some(person) => person,
+            None => return Err(EvalError::InvalidAuthorIndex(index)),
+        };
+        authors.push(person.clone());
+    }
+    Ok(Document {
+        id: deduped.id,
+        name: deduped.name,
+        authors: authors,
+        content: deduped.content,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_deduped, encode_deduped};
+    use {make_people, Document, EvalError};
+
+    fn make_repeated_authors(slots: usize, unique: usize) -> Document {
+        let people = make_people(unique);
+        Document {
+            id: 1,
+            name: "Repeated".to_string(),
+            authors: (0..slots).map(|i| people[i % unique].clone()).collect(),
+            content: vec![1, 2, 3],
+        }
+    }
+
+    #[test]
+    fn sizes() {
+        println!("");
+        println!("Size of 1000 author slots referencing 3 people:");
+        println!("Using Bincode format with serde");
+        let document = make_repeated_authors(1000, 3);
+        let plain = ::serde_and_bincode::encode(&document).len();
+        let deduped = encode_deduped(&document);
+        assert_eq!(decode_deduped(&deduped).unwrap(), document);
+        assert!(deduped.len() < plain / 3);
+        println!("    plain:   {} bytes", plain);
+        println!("    deduped: {} bytes", deduped.len());
+    }
+
+    #[test]
+    fn invalid_index() {
+        let mut bytes = encode_deduped(&make_repeated_authors(4, 2));
+        // The author indices come just before the content, which is a length then 3 bytes.
+        let last_index = bytes.len() - 3 - 8 - 4;
+        bytes[last_index + 3] = 2;
+        assert_err!(decode_deduped(&bytes), EvalError::InvalidAuthorIndex(2));
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt;
+use Document;
+
+/// A single way in which two documents differ.
+#[derive(Debug, PartialEq)]
+pub enum FieldDiff {
+    Id {
+        left: u64,
+        right: u64,
+    },
+    Name {
+        left: String,
+        right: String,
+    },
+    AuthorCount {
+        left: usize,
+        right: usize,
+    },
+    Author {
+        index: usize,
+        left: String,
+        right: String,
+    },
+    ContentLength {
+        left: usize,
+        right: usize,
+    },
+    /// The first offset at which the contents differ, looking only at their common length.
+    ContentByte {
+        offset: usize,
+        left: u8,
+        right: u8,
+    },
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FieldDiff::Id { left, right } => write!(formatter, "id: {} != {}", left, right),
+            FieldDiff::Name { ref left, ref right } => {
+                write!(formatter, "name: {:?} != {:?}", left, right)
+            }
+            FieldDiff::AuthorCount { left, right } => {
+                write!(formatter, "author count: {} != {}", left, right)
+            }
+            FieldDiff::Author { index, ref left, ref right } => {
+                write!(formatter, "author {}: {} != {}", index, left, right)
+            }
+            FieldDiff::ContentLength { left, right } => {
+                write!(formatter, "content length: {} != {}", left, right)
+            }
+            FieldDiff::ContentByte { offset, left, right } => {
+                write!(formatter, "content byte {}: {} != {}", offset, left, right)
+            }
+        }
+    }
+}
+
+/// Lists every field in which `left` and `right` differ.
+pub fn compare_documents(left: &Document, right: &Document) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+    if left.id != right.id {
+        diffs.push(FieldDiff::Id {
+            left: left.id,
+            right: right.id,
+        });
+    }
+    if left.name != right.name {
+        diffs.push(FieldDiff::Name {
+            left: left.name.clone(),
+            right: right.name.clone(),
+        });
+    }
+    if left.authors.len() != right.authors.len() {
+        diffs.push(FieldDiff::AuthorCount {
+            left: left.authors.len(),
+            right: right.authors.len(),
+        });
+    }
+    for (index, (left, right)) in left.authors.iter().zip(right.authors.iter()).enumerate() {
+        if left != right {
+            diffs.push(FieldDiff::Author {
+                index: index,
+                left: format!("{:?}", left),
+                right: format!("{:?}", right),
+            });
+        }
+    }
+    if left.content.len() != right.content.len() {
+        diffs.push(FieldDiff::ContentLength {
+            left: left.content.len(),
+            right: right.content.len(),
+        });
+    }
+    let first_difference = left.content
+                               .iter()
+                               .zip(right.content.iter())
+                               .position(|(left, right)| left != right);
+    if let Some(offset) = first_difference {
+        diffs.push(FieldDiff::ContentByte {
+            offset: offset,
+            left: left.content[offset],
+            right: right.content[offset],
+        });
+    }
+    diffs
+}
+
+/// Joins `diffs` into a single line, for use in assertion messages.
+pub fn describe(diffs: &[FieldDiff]) -> String {
+    let descriptions: Vec<_> = diffs.iter().map(|diff| diff.to_string()).collect();
+    descriptions.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare_documents, describe, FieldDiff};
+    use {make_sample_data, Document};
+
+    #[test]
+    fn identical() {
+        let document = make_sample_data(1024);
+        let copy: Document = ::serde_and_bincode::decode(&::serde_and_bincode::encode(&document));
+        assert!(compare_documents(&document, &copy).is_empty());
+    }
+
+    #[test]
+    fn differences() {
+        let left = make_sample_data(1024);
+        let mut right: Document = ::serde_and_bincode::decode(&::serde_and_bincode::encode(&left));
+        right.id += 1;
+        right.authors[1].name.push('!');
+        right.content[100] = right.content[100].wrapping_add(1);
+        right.content.push(0);
+
+        let diffs = compare_documents(&left, &right);
+        assert_eq!(diffs.len(), 4);
+        assert_eq!(diffs[0],
+                   FieldDiff::Id {
+                       left: left.id,
+                       right: left.id + 1,
+                   });
+        match diffs[1] {
+            FieldDiff::Author { index: 1, .. } => (),
+            ref diff => panic!("unexpected diff: {}", diff),
+        }
+        assert_eq!(diffs[2],
+                   FieldDiff::ContentLength {
+                       left: 1024,
+                       right: 1025,
+                   });
+        assert_eq!(diffs[3],
+                   FieldDiff::ContentByte {
+                       offset: 100,
+                       left: left.content[100],
+                       right: left.content[100].wrapping_add(1),
+                   });
+        assert!(describe(&diffs).starts_with("id: "));
+    }
+}
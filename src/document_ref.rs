@@ -0,0 +1,91 @@
+use borrow_map::{read_bytes, read_str, read_u64};
+use person_ref::{decode_people_borrowed, PersonRef};
+use EvalError;
+
+/// Document whose strings and content borrow from the buffer it was decoded from.  This is the
+/// shape newer serde derives with `#[serde(borrow)]`; serde 0.6 has no such attribute and always
+/// deserializes into owned values, so the decoder below stands in for it.
+#[derive(Debug, PartialEq)]
+pub struct DocumentRef<'a> {
+    pub id: u64,
+    pub name: &'a str,
+    pub authors: Vec<PersonRef<'a>>,
+    pub content: &'a [u8],
+}
+
+/// Decodes a bincode-encoded `Document` without copying its strings or content.
+pub fn decode_document_borrowed(mut bytes: &[u8]) -> Result<DocumentRef, EvalError> {
+    let id = try!(read_u64(&mut bytes));
+    let name = try!(read_str(&mut bytes));
+    let authors = try!(decode_people_borrowed(&mut bytes));
+    let content = try!(read_bytes(&mut bytes));
+    Ok(DocumentRef {
+        id: id,
+        name: name,
+        authors: authors,
+        content: content,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode_document_borrowed;
+    use test::Bencher;
+    use {make_sample_data, EvalError};
+
+    #[test]
+    fn matches_owned() {
+        let document = make_sample_data(1024);
+        let bytes = ::serde_and_bincode::encode(&document);
+        let borrowed = decode_document_borrowed(&bytes).unwrap();
+        assert_eq!(borrowed.id, document.id);
+        assert_eq!(borrowed.name, document.name);
+        assert_eq!(borrowed.content, &document.content[..]);
+        assert_eq!(borrowed.authors.len(), document.authors.len());
+        for (borrowed, owned) in borrowed.authors.iter().zip(&document.authors) {
+            assert_eq!(borrowed.name, owned.name);
+            assert_eq!(borrowed.email, owned.email);
+        }
+        let range = bytes.as_ptr() as usize..bytes.as_ptr() as usize + bytes.len();
+        for address in &[borrowed.name.as_ptr() as usize, borrowed.content.as_ptr() as usize] {
+            assert!(*address >= range.start && *address < range.end);
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = ::serde_and_bincode::encode(&make_sample_data(1024));
+        assert_err!(decode_document_borrowed(&bytes[..bytes.len() - 1]), EvalError::TruncatedInput);
+    }
+
+    #[cfg(feature = "counting_alloc")]
+    #[test]
+    fn decode_allocations() {
+        use counting_allocator::allocations;
+        use Document;
+        println!("");
+        println!("Allocations while decoding the big document:");
+        let bytes = ::serde_and_bincode::encode(&make_sample_data(1024 * 1024));
+
+        let before = allocations();
+        ::serde_and_bincode::decode::<Document>(&bytes);
+        let owned_allocations = allocations() - before;
+
+        let before = allocations();
+        decode_document_borrowed(&bytes).unwrap();
+        let borrowed_allocations = allocations() - before;
+
+        // Only the authors vector allocates.
+        assert_eq!(borrowed_allocations, 1);
+        println!("    Document:       {}", owned_allocations);
+        println!("    DocumentRef:    {}", borrowed_allocations);
+    }
+
+    // Compare against `serde_and_bincode_decode_big`, which decodes the same bytes into an owned
+    // `Document`.
+    #[bench]
+    fn bincode_decode_big_borrowed(bencher: &mut Bencher) {
+        let bytes = ::serde_and_bincode::encode(&make_sample_data(1024 * 1024));
+        bencher.iter(|| decode_document_borrowed(&bytes).unwrap().content.len())
+    }
+}
@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use cold_cache::micros;
+    use many_authors::make_many_authors;
+    use {make_sample_data, Document};
+
+    const ITERATIONS: u32 = 20;
+
+    fn mean_decode_and_drop(decode: &Fn() -> Document) -> (Duration, Duration) {
+        let mut decoding = Duration::new(0, 0);
+        let mut dropping = Duration::new(0, 0);
+        for _ in 0..ITERATIONS {
+            let start = Instant::now();
+            let decoded = decode();
+            decoding = decoding + start.elapsed();
+            let start = Instant::now();
+            drop(decoded);
+            dropping = dropping + start.elapsed();
+        }
+        (decoding / ITERATIONS, dropping / ITERATIONS)
+    }
+
+    // A bench's timing includes dropping whatever each iteration returns, so this times the two
+    // separately.  Run with `cargo test --release drop_cost -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn drop_cost() {
+        println!("");
+        println!("Mean time to decode and then drop a document:");
+        let documents = [("big document", make_sample_data(1024 * 1024)),
+                         ("10000 authors", make_many_authors(10_000))];
+        for codec in ::codec::all::<Document>() {
+            println!("Using {}", codec.name());
+            for &(label, ref document) in &documents {
+                let bytes = codec.encode(document).unwrap();
+                let (decode, drop) = mean_decode_and_drop(&|| codec.decode(&bytes).unwrap());
+                println!("    {:14} decode: {} us, drop: {} us",
+                         label,
+                         micros(decode),
+                         micros(drop));
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+/// Three levels of vectors, to show what each level costs when it's empty.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct Nested {
+    pub rows: Vec<Vec<Vec<u8>>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Nested;
+
+    const EMPTY_COUNT: usize = 1000;
+
+    fn shapes() -> Vec<(&'static str, Nested)> {
+        vec![("no rows", Nested { rows: vec![] }),
+             ("all empty, 3 deep", Nested { rows: vec![vec![vec![]]] }),
+             ("mixed",
+              Nested {
+                 rows: vec![vec![],
+                            vec![vec![], vec![1, 2, 3]],
+                            vec![vec![]; 3],
+                            vec![vec![4], vec![], vec![5, 6]]],
+             }),
+             ("1000 empty cells", Nested { rows: vec![vec![vec![]; EMPTY_COUNT]] }),
+             ("1000 empty rows", Nested { rows: vec![vec![]; EMPTY_COUNT] })]
+    }
+
+    #[test]
+    fn sizes() {
+        println!("");
+        println!("Size of nested vectors, mostly empty:");
+        // bincode writes a u64 length for every vector, however empty.  The mixed shape has 4 rows
+        // holding 8 cells holding 6 bytes.
+        let bincode_sizes = [8,
+                             24,
+                             8 + 4 * 8 + 8 * 8 + 6,
+                             16 + 8 * EMPTY_COUNT,
+                             8 + 8 * EMPTY_COUNT];
+        for codec in ::codec::all::<Nested>() {
+            println!("Using {}", codec.name());
+            let mut sizes = Vec::new();
+            for (label, nested) in shapes() {
+                let bytes = codec.encode(&nested).unwrap();
+                assert!(codec.decode(&bytes).unwrap() == nested,
+                        "{} mangled {}",
+                        codec.name(),
+                        label);
+                println!("    {}: {} bytes", label, bytes.len());
+                sizes.push(bytes.len());
+            }
+            let per_cell = (sizes[3] - sizes[1]) as f64 / (EMPTY_COUNT - 1) as f64;
+            let per_row = (sizes[4] - sizes[0]) as f64 / EMPTY_COUNT as f64;
+            println!("    per extra empty cell: {:.2} bytes", per_cell);
+            println!("    per empty row:        {:.2} bytes", per_row);
+            if codec.name().ends_with("bincode") {
+                assert_eq!(sizes, bincode_sizes);
+            }
+        }
+    }
+}
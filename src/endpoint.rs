@@ -0,0 +1,185 @@
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use rustc_serialize::{Decodable, Decoder, Encodable, Encoder};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A socket address encoded as its IP octets (4 or 16 of them) followed by the port.  Neither
+/// library supports `SocketAddr` itself.  The IPv6 flow info and scope id aren't kept.
+#[derive(Debug, PartialEq)]
+pub struct PackedAddr(pub SocketAddr);
+
+/// A socket address encoded as a string such as "1.2.3.4:80" or "[::1]:80".
+#[derive(Debug, PartialEq)]
+pub struct TextAddr(pub SocketAddr);
+
+const INVALID_OCTETS: &'static str = "IP address must have 4 or 16 octets";
+const INVALID_TEXT: &'static str = "invalid socket address";
+
+impl PackedAddr {
+    fn to_parts(&self) -> (Vec<u8>, u16) {
+        match self.0 {
+            SocketAddr::V4(ref addr) => (addr.ip().octets().to_vec(), addr.port()),
+            SocketAddr::V6(ref addr) => {
+                let mut octets = Vec::with_capacity(16);
+                for segment in &addr.ip().segments() {
+                    octets.push((segment >> 8) as u8);
+                    octets.push(*segment as u8);
+                }
+                (octets, addr.port())
+            }
+        }
+    }
+
+    fn from_parts(octets: &[u8], port: u16) -> Option<PackedAddr> {
+        match octets.len() {
+            4 => {
+                let ip = Ipv4Addr::new(octets[0], octets[1], octets[2], octets[3]);
+                Some(PackedAddr(SocketAddr::V4(SocketAddrV4::new(ip, port))))
+            }
+            16 => {
+                let mut segments = [0u16; 8];
+                for (segment, pair) in segments.iter_mut().zip(octets.chunks(2)) {
+                    *segment = (pair[0] as u16) << 8 | pair[1] as u16;
+                }
+                let ip = Ipv6Addr::new(segments[0],
+                                       segments[1],
+                                       segments[2],
+                                       segments[3],
+                                       segments[4],
+                                       segments[5],
+                                       segments[6],
+                                       segments[7]);
+                Some(PackedAddr(SocketAddr::V6(SocketAddrV6::new(ip, port, 0, 0))))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for PackedAddr {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        self.to_parts().serialize(serializer)
+    }
+}
+
+impl Deserialize for PackedAddr {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<PackedAddr, D::Error> {
+        let (octets, port): (Vec<u8>, u16) = try!(Deserialize::deserialize(deserializer));
+        PackedAddr::from_parts(&octets, port)
+            .ok_or_else(|| D::Error::invalid_value(INVALID_OCTETS))
+    }
+}
+
+impl Encodable for PackedAddr {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), E::Error> {
+        self.to_parts().encode(encoder)
+    }
+}
+
+impl Decodable for PackedAddr {
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<PackedAddr, D::Error> {
+        let (octets, port): (Vec<u8>, u16) = try!(Decodable::decode(decoder));
+        PackedAddr::from_parts(&octets, port).ok_or_else(|| decoder.error(INVALID_OCTETS))
+    }
+}
+
+impl Serialize for TextAddr {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        self.0.to_string().serialize(serializer)
+    }
+}
+
+impl Deserialize for TextAddr {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<TextAddr, D::Error> {
+        let text: String = try!(Deserialize::deserialize(deserializer));
+        text.parse().map(TextAddr).map_err(|_| D::Error::invalid_value(INVALID_TEXT))
+    }
+}
+
+impl Encodable for TextAddr {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), E::Error> {
+        self.0.to_string().encode(encoder)
+    }
+}
+
+impl Decodable for TextAddr {
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<TextAddr, D::Error> {
+        let text: String = try!(Decodable::decode(decoder));
+        text.parse().map(TextAddr).map_err(|_| decoder.error(INVALID_TEXT))
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct Endpoint {
+    addr: PackedAddr,
+    name: String,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct TextEndpoint {
+    addr: TextAddr,
+    name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use super::{Endpoint, PackedAddr, TextAddr, TextEndpoint};
+
+    fn addresses() -> Vec<SocketAddr> {
+        vec!["1.2.3.4:80".parse().unwrap(),
+             "[2001:db8::ff00:42:8329]:8080".parse().unwrap(),
+             "[::1]:0".parse().unwrap()]
+    }
+
+    fn make_endpoints(addr: SocketAddr) -> (Endpoint, TextEndpoint) {
+        let packed = Endpoint {
+            addr: PackedAddr(addr),
+            name: "server".to_string(),
+        };
+        let text = TextEndpoint {
+            addr: TextAddr(addr),
+            name: "server".to_string(),
+        };
+        (packed, text)
+    }
+
+    #[test]
+    fn round_trips() {
+        let codecs = ::codec::all::<Endpoint>().into_iter().zip(::codec::all::<TextEndpoint>());
+        for (packed_codec, text_codec) in codecs {
+            for addr in addresses() {
+                let (packed, text) = make_endpoints(addr);
+                let bytes = packed_codec.encode(&packed).unwrap();
+                assert_eq!(packed_codec.decode(&bytes).unwrap(), packed);
+                let bytes = text_codec.encode(&text).unwrap();
+                assert_eq!(text_codec.decode(&bytes).unwrap(), text);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_octets() {
+        let bytes = ::serde_and_bincode::encode(&(vec![1u8, 2, 3], 80u16, "server"));
+        assert!(::serde_and_bincode::decode_result::<Endpoint>(&bytes).is_err());
+        let bytes = ::rustc_and_bincode::encode(&(vec![1u8, 2, 3], 80u16, "server"));
+        assert!(::rustc_and_bincode::decode_result::<Endpoint>(&bytes).is_err());
+    }
+
+    #[test]
+    fn sizes() {
+        println!("");
+        println!("Size of an endpoint with its address packed and as text:");
+        let codecs = ::codec::all::<Endpoint>().into_iter().zip(::codec::all::<TextEndpoint>());
+        for (packed_codec, text_codec) in codecs {
+            println!("Using {}", packed_codec.name());
+            for addr in addresses() {
+                let (packed, text) = make_endpoints(addr);
+                println!("    {:32} packed: {:3} bytes, text: {:3} bytes",
+                         addr.to_string(),
+                         packed_codec.encode(&packed).unwrap().len(),
+                         text_codec.encode(&text).unwrap().len());
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use codec::Codec;
+use control_msg::ControlMsg;
+use varint;
+use {Document, EvalError, Person};
+
+/// Any of the messages which can share a channel.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub enum Message {
+    Doc(Document),
+    Person(Person),
+    Control(ControlMsg),
+}
+
+/// Appends `message` to `stream`, encoded with `codec` and prefixed by its length as a varint.
+pub fn write_message(codec: &Codec<Message>,
+                     message: &Message,
+                     stream: &mut Vec<u8>)
+                     -> Result<(), EvalError> {
+    let payload = try!(codec.encode(message));
+    varint::write_u64(stream, payload.len() as u64);
+    stream.extend_from_slice(&payload);
+    Ok(())
+}
+
+/// Decodes one message written by `write_message` from the front of `stream`, advancing it past
+/// the message.
+pub fn read_message(codec: &Codec<Message>, stream: &mut &[u8]) -> Result<Message, EvalError> {
+    let (len, consumed) = try!(varint::read_u64(stream));
+    let remaining = &stream[consumed..];
+    if (remaining.len() as u64) < len {
+        return Err(EvalError::TruncatedFrame);
+    }
+    let (payload, rest) = remaining.split_at(len as usize);
+    let message = try!(codec.decode(payload));
+    *stream = rest;
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_message, write_message, Message};
+    use control_msg::make_control_msg;
+    use {make_people, make_sample_data};
+
+    fn make_messages() -> Vec<Message> {
+        let mut people = make_people(2);
+        vec![Message::Control(make_control_msg(1)),
+             Message::Doc(make_sample_data(1024)),
+             Message::Person(people.pop().unwrap()),
+             Message::Control(make_control_msg(2)),
+             Message::Person(people.pop().unwrap()),
+             Message::Doc(make_sample_data(0))]
+    }
+
+    #[test]
+    fn mixed_stream() {
+        let messages = make_messages();
+        for codec in ::codec::all::<Message>() {
+            let mut stream = Vec::new();
+            for message in &messages {
+                write_message(codec, message, &mut stream).unwrap();
+            }
+            let mut remaining = &stream[..];
+            let mut decoded = Vec::new();
+            while !remaining.is_empty() {
+                decoded.push(read_message(codec, &mut remaining).unwrap());
+            }
+            assert!(decoded == messages, "{} mangled the stream", codec.name());
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::str::Utf8Error;
+
+use bincode::rustc_serialize::{DecodingError, EncodingError};
+use bincode::serde::{DeserializeError, SerializeError};
+use cbor::CborError;
+use rustc_serialize::base64::FromBase64Error;
+use serde_json;
+
+#[derive(Debug)]
+pub enum EvalError {
+    Cbor(CborError),
+    BincodeSerialize(SerializeError),
+    BincodeDeserialize(DeserializeError),
+    BincodeEncoding(EncodingError),
+    BincodeDecoding(DecodingError),
+    Json(serde_json::Error),
+    Utf8(Utf8Error),
+    Decompress(io::Error),
+    Base64(FromBase64Error),
+    TruncatedChunk,
+    ChunkOutOfOrder {
+        expected: u32,
+        got: u32,
+    },
+    ChunkCountMismatch {
+        expected: u32,
+        got: u32,
+    },
+    NoCodecs,
+    UnknownCodec(u8),
+    UnknownCompression(u8),
+    TruncatedFrame,
+    InvalidFrameLength,
+    FrameTooLong {
+        len: u64,
+        max: u64,
+    },
+    TruncatedVarint,
+    InvalidVarint,
+    InvalidAuthorIndex(u32),
+    TruncatedHeader,
+    TruncatedInput,
+    BadMagic,
+    UnsupportedVersion {
+        expected: u8,
+        got: u8,
+    },
+    ContentRead(io::Error),
+    UnexpectedCbor(u8),
+    ChunkChecksumMismatch(u32),
+    InvalidStringIndex(u32),
+    InvalidNanos(u32),
+    TimeOutOfRange,
+    StringTooLong(u64),
+    TooDeeplyNested,
+    DecompressedTooLong(u64),
+    TooManyChunks(u64),
+}
+
+impl From<CborError> for EvalError {
+    fn from(error: CborError) -> EvalError {
+        EvalError::Cbor(error)
+    }
+}
+
+impl From<SerializeError> for EvalError {
+    fn from(error: SerializeError) -> EvalError {
+        EvalError::BincodeSerialize(error)
+    }
+}
+
+impl From<DeserializeError> for EvalError {
+    fn from(error: DeserializeError) -> EvalError {
+        EvalError::BincodeDeserialize(error)
+    }
+}
+
+impl From<EncodingError> for EvalError {
+    fn from(error: EncodingError) -> EvalError {
+        EvalError::BincodeEncoding(error)
+    }
+}
+
+impl From<DecodingError> for EvalError {
+    fn from(error: DecodingError) -> EvalError {
+        EvalError::BincodeDecoding(error)
+    }
+}
+
+impl From<serde_json::Error> for EvalError {
+    fn from(error: serde_json::Error) -> EvalError {
+        EvalError::Json(error)
+    }
+}
+
+impl From<Utf8Error> for EvalError {
+    fn from(error: Utf8Error) -> EvalError {
+        EvalError::Utf8(error)
+    }
+}
+
+impl From<FromBase64Error> for EvalError {
+    fn from(error: FromBase64Error) -> EvalError {
+        EvalError::Base64(error)
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EvalError::Cbor(ref error) => write!(formatter, "CBOR error: {}", error),
+            EvalError::BincodeSerialize(ref error) => {
+                write!(formatter, "Bincode serialize error: {}", error)
+            }
+            EvalError::BincodeDeserialize(ref error) => {
+                write!(formatter, "Bincode deserialize error: {}", error)
+            }
+            EvalError::BincodeEncoding(ref error) => {
+                write!(formatter, "Bincode encoding error: {}", error)
+            }
+            EvalError::BincodeDecoding(ref error) => {
+                write!(formatter, "Bincode decoding error: {}", error)
+            }
+            EvalError::Json(ref error) => write!(formatter, "JSON error: {}", error),
+            EvalError::Utf8(ref error) => write!(formatter, "UTF-8 error: {}", error),
+            EvalError::Decompress(ref error) => write!(formatter, "Decompression error: {}", error),
+            EvalError::Base64(ref error) => write!(formatter, "Base64 error: {}", error),
+            EvalError::TruncatedChunk => write!(formatter, "Chunk is too short for its header"),
+            EvalError::ChunkOutOfOrder { expected, got } => {
+                write!(formatter, "Expected chunk {}, got chunk {}", expected, got)
+            }
+            EvalError::ChunkCountMismatch { expected, got } => {
+                write!(formatter, "Expected {} chunks, got {}", expected, got)
+            }
+            EvalError::NoCodecs => write!(formatter, "No codecs to choose from"),
+            EvalError::UnknownCodec(id) => write!(formatter, "Unknown codec id {}", id),
+            EvalError::UnknownCompression(flag) => {
+                write!(formatter, "Unknown compression flag {}", flag)
+            }
+            EvalError::TruncatedFrame => {
+                write!(formatter, "Frame is shorter than its length prefix")
+            }
+            EvalError::InvalidFrameLength => write!(formatter, "Frame length prefix is too long"),
+            EvalError::FrameTooLong { len, max } => {
+                write!(formatter, "Frame of {} bytes exceeds the maximum of {}", len, max)
+            }
+            EvalError::TruncatedVarint => write!(formatter, "Varint ends before its last byte"),
+            EvalError::InvalidVarint => write!(formatter, "Varint doesn't fit in 64 bits"),
+            EvalError::InvalidAuthorIndex(index) => {
+                write!(formatter, "Author index {} is past the end of the table", index)
+            }
+            EvalError::TruncatedHeader => write!(formatter, "Input is too short for its header"),
+            EvalError::TruncatedInput => write!(formatter, "Input ends part way through a value"),
+            EvalError::BadMagic => write!(formatter, "Header doesn't start with the magic number"),
+            EvalError::UnsupportedVersion { expected, got } => {
+                write!(formatter, "Expected format version {}, got {}", expected, got)
+            }
+            EvalError::ContentRead(ref error) => {
+                write!(formatter, "Error reading content: {}", error)
+            }
+            EvalError::UnexpectedCbor(initial) => {
+                write!(formatter, "Unexpected CBOR item with initial byte {:#04x}", initial)
+            }
+            EvalError::ChunkChecksumMismatch(index) => {
+                write!(formatter, "Chunk {} doesn't match its checksum", index)
+            }
+            EvalError::InvalidStringIndex(index) => {
+                write!(formatter, "String index {} is past the end of the table", index)
+            }
+            EvalError::InvalidNanos(nanos) => {
+                write!(formatter, "{} nanoseconds is more than a whole second", nanos)
+            }
+            EvalError::TimeOutOfRange => write!(formatter, "Time is outside the supported range"),
+            EvalError::StringTooLong(len) => {
+                write!(formatter, "String of {} bytes is too long for a u32 length", len)
+            }
+            EvalError::TooDeeplyNested => write!(formatter, "Input is nested too deeply"),
+            EvalError::DecompressedTooLong(limit) => {
+                write!(formatter, "Decompressed data is longer than the {} byte limit", limit)
+            }
+            EvalError::TooManyChunks(count) => {
+                write!(formatter, "{} chunks is too many for a u32 count", count)
+            }
+        }
+    }
+}
+
+impl Error for EvalError {
+    fn description(&self) -> &str {
+        match *self {
+            EvalError::Cbor(ref error) => error.description(),
+            EvalError::BincodeSerialize(ref error) => error.description(),
+            EvalError::BincodeDeserialize(ref error) => error.description(),
+            EvalError::BincodeEncoding(ref error) => error.description(),
+            EvalError::BincodeDecoding(ref error) => error.description(),
+            EvalError::Json(ref error) => error.description(),
+            EvalError::Utf8(ref error) => error.description(),
+            EvalError::Decompress(ref error) => error.description(),
+            EvalError::Base64(ref error) => error.description(),
+            EvalError::TruncatedChunk => "truncated chunk",
+            EvalError::ChunkOutOfOrder { .. } => "chunk out of order",
+            EvalError::ChunkCountMismatch { .. } => "chunk count mismatch",
+            EvalError::NoCodecs => "no codecs",
+            EvalError::UnknownCodec(_) => "unknown codec",
+            EvalError::UnknownCompression(_) => "unknown compression",
+            EvalError::TruncatedFrame => "truncated frame",
+            EvalError::InvalidFrameLength => "invalid frame length",
+            EvalError::FrameTooLong { .. } => "frame too long",
+            EvalError::TruncatedVarint => "truncated varint",
+            EvalError::InvalidVarint => "invalid varint",
+            EvalError::InvalidAuthorIndex(_) => "invalid author index",
+            EvalError::TruncatedHeader => "truncated header",
+            EvalError::TruncatedInput => "truncated input",
+            EvalError::BadMagic => "bad magic number",
+            EvalError::UnsupportedVersion { .. } => "unsupported version",
+            EvalError::ContentRead(ref error) => error.description(),
+            EvalError::UnexpectedCbor(_) => "unexpected CBOR item",
+            EvalError::ChunkChecksumMismatch(_) => "chunk checksum mismatch",
+            EvalError::InvalidStringIndex(_) => "invalid string index",
+            EvalError::InvalidNanos(_) => "invalid nanoseconds",
+            EvalError::TimeOutOfRange => "time out of range",
+            EvalError::StringTooLong(_) => "string too long",
+            EvalError::TooDeeplyNested => "too deeply nested",
+            EvalError::DecompressedTooLong(_) => "decompressed data too long",
+            EvalError::TooManyChunks(_) => "too many chunks",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            EvalError::Cbor(ref error) => Some(error),
+            EvalError::BincodeSerialize(ref error) => Some(error),
+            EvalError::BincodeDeserialize(ref error) => Some(error),
+            EvalError::BincodeEncoding(ref error) => Some(error),
+            EvalError::BincodeDecoding(ref error) => Some(error),
+            EvalError::Json(ref error) => Some(error),
+            EvalError::Utf8(ref error) => Some(error),
+            EvalError::Decompress(ref error) => Some(error),
+            EvalError::Base64(ref error) => Some(error),
+            EvalError::ContentRead(ref error) => Some(error),
+            EvalError::TruncatedChunk |
+            EvalError::ChunkOutOfOrder { .. } |
+            EvalError::ChunkCountMismatch { .. } |
+            EvalError::NoCodecs |
+            EvalError::UnknownCodec(_) |
+            EvalError::UnknownCompression(_) |
+            EvalError::TruncatedFrame |
+            EvalError::InvalidFrameLength |
+            EvalError::FrameTooLong { .. } |
+            EvalError::TruncatedVarint |
+            EvalError::InvalidVarint |
+            EvalError::InvalidAuthorIndex(_) |
+            EvalError::TruncatedHeader |
+            EvalError::TruncatedInput |
+            EvalError::BadMagic |
+            EvalError::UnsupportedVersion { .. } |
+            EvalError::UnexpectedCbor(_) |
+            EvalError::ChunkChecksumMismatch(_) |
+            EvalError::InvalidStringIndex(_) |
+            EvalError::InvalidNanos(_) |
+            EvalError::TimeOutOfRange |
+            EvalError::StringTooLong(_) |
+            EvalError::TooDeeplyNested |
+            EvalError::DecompressedTooLong(_) |
+            EvalError::TooManyChunks(_) => None,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use {Document, Person};
+
+/// Encodes like `Document`, but with the fields in the opposite order, for the self-describing
+/// codecs.
+#[derive(Serialize)]
+pub struct ReversedDocument {
+    content: Vec<u8>,
+    authors: Vec<Person>,
+    name: String,
+    id: u64,
+}
+
+impl From<Document> for ReversedDocument {
+    fn from(document: Document) -> ReversedDocument {
+        ReversedDocument {
+            content: document.content,
+            authors: document.authors,
+            name: document.name,
+            id: document.id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReversedDocument;
+    use test::Bencher;
+    use {make_sample_data, make_seeded_sample_data, Document};
+
+    #[test]
+    fn decodes_reversed() {
+        let document = make_seeded_sample_data(1024);
+        let reversed = ReversedDocument::from(make_seeded_sample_data(1024));
+        let reversed = ::serde_and_json::encode(&reversed);
+        assert!(reversed.starts_with(b"{\"content\":"));
+        assert_eq!(::serde_and_json::decode::<Document>(&reversed), document);
+    }
+
+    // The derived `Deserialize` holds each field in its own local until the end of the object, so
+    // fields can arrive in any order without being buffered or reparsed.
+    #[bench]
+    fn serde_and_json_decode_declaration_order(bencher: &mut Bencher) {
+        let bytes = ::serde_and_json::encode(&make_sample_data(1024));
+        bencher.iter(|| ::serde_and_json::decode::<Document>(&bytes))
+    }
+
+    #[bench]
+    fn serde_and_json_decode_reversed_order(bencher: &mut Bencher) {
+        let bytes = ::serde_and_json::encode(&ReversedDocument::from(make_sample_data(1024)));
+        bencher.iter(|| ::serde_and_json::decode::<Document>(&bytes))
+    }
+}
@@ -0,0 +1,118 @@
+/// Sixteen independent flags, each encoded as its own bool.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct Flags {
+    pub a: bool,
+    pub b: bool,
+    pub c: bool,
+    pub d: bool,
+    pub e: bool,
+    pub f: bool,
+    pub g: bool,
+    pub h: bool,
+    pub i: bool,
+    pub j: bool,
+    pub k: bool,
+    pub l: bool,
+    pub m: bool,
+    pub n: bool,
+    pub o: bool,
+    pub p: bool,
+}
+
+/// The same flags packed into a bitfield, with `a` in the lowest bit.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct PackedFlags {
+    pub bits: u16,
+}
+
+impl Flags {
+    fn as_array(&self) -> [bool; 16] {
+        [self.a, self.b, self.c, self.d, self.e, self.f, self.g, self.h, self.i, self.j, self.k,
+         self.l, self.m, self.n, self.o, self.p]
+    }
+
+    fn from_array(flags: [bool; 16]) -> Flags {
+        Flags {
+            a: flags[0],
+            b: flags[1],
+            c: flags[2],
+            d: flags[3],
+            e: flags[4],
+            f: flags[5],
+            g: flags[6],
+            h: flags[7],
+            i: flags[8],
+            j: flags[9],
+            k: flags[10],
+            l: flags[11],
+            m: flags[12],
+            n: flags[13],
+            o: flags[14],
+            p: flags[15],
+        }
+    }
+
+    pub fn pack(&self) -> PackedFlags {
+        let bits = self.as_array()
+                       .iter()
+                       .enumerate()
+                       .fold(0, |bits, (index, &flag)| bits | (flag as u16) << index);
+        PackedFlags { bits: bits }
+    }
+}
+
+impl PackedFlags {
+    pub fn unpack(&self) -> Flags {
+        let mut flags = [false; 16];
+        for (index, flag) in flags.iter_mut().enumerate() {
+            *flag = self.bits & 1 << index != 0;
+        }
+        Flags::from_array(flags)
+    }
+}
+
+/// Returns flags with every third one set.
+pub fn make_flags() -> Flags {
+    let mut flags = [false; 16];
+    for (index, flag) in flags.iter_mut().enumerate() {
+        *flag = index % 3 == 0;
+    }
+    Flags::from_array(flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{make_flags, Flags, PackedFlags};
+
+    #[test]
+    fn round_trips() {
+        let flags = make_flags();
+        let packed = flags.pack();
+        assert_eq!(packed.bits, 0b1001_0010_0100_1001);
+        assert_eq!(packed.unpack(), flags);
+        let codecs = ::codec::all::<Flags>().into_iter().zip(::codec::all::<PackedFlags>());
+        for (flags_codec, packed_codec) in codecs {
+            let bytes = flags_codec.encode(&flags).unwrap();
+            assert_eq!(flags_codec.decode(&bytes).unwrap(), flags);
+            let bytes = packed_codec.encode(&packed).unwrap();
+            assert_eq!(packed_codec.decode(&bytes).unwrap(), packed);
+        }
+    }
+
+    #[test]
+    fn sizes() {
+        println!("");
+        println!("Size of 16 flags as bools and packed into a u16:");
+        let flags = make_flags();
+        let packed = flags.pack();
+        let codecs = ::codec::all::<Flags>().into_iter().zip(::codec::all::<PackedFlags>());
+        for (flags_codec, packed_codec) in codecs {
+            println!("    {}: bools: {} bytes, packed: {} bytes",
+                     flags_codec.name(),
+                     flags_codec.encode(&flags).unwrap().len(),
+                     packed_codec.encode(&packed).unwrap().len());
+        }
+        assert_eq!(::serde_and_bincode::encode(&flags).len(), 16);
+        assert_eq!(::serde_and_bincode::encode(&packed).len(), 2);
+    }
+}
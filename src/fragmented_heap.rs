@@ -0,0 +1,52 @@
+/// Allocates `count` small blocks of assorted sizes and frees every other one, leaving the heap
+/// full of holes too small for most later allocations.  The survivors are returned and must be
+/// kept alive for the fragmentation to last.
+pub fn fragment_heap(count: usize) -> Vec<Vec<u8>> {
+    let blocks: Vec<Vec<u8>> = (0..count).map(|i| vec![i as u8; 16 + (i * 37) % 241]).collect();
+    blocks.into_iter().enumerate().filter(|&(i, _)| i % 2 == 0).map(|(_, block)| block).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fragment_heap;
+    use bench::bench_encode;
+    use codec::{self, Codec};
+    use test::Bencher;
+    use {make_sample_data, Document};
+
+    // Around 26 MiB of blocks from 16 to 256 bytes, half of which are freed again.
+    const BLOCK_COUNT: usize = 200_000;
+
+    #[test]
+    fn keeps_every_other_block() {
+        let blocks = fragment_heap(10);
+        assert_eq!(blocks.len(), 5);
+        assert!(blocks.iter().enumerate().all(|(i, block)| block[0] == (i * 2) as u8));
+    }
+
+    // Compare against the `*_encode_big` benches, which encode the same document on a clean heap.
+    fn bench_encode_big_fragmented<C: Codec<Document>>(bencher: &mut Bencher, codec: &C) {
+        let _blocks = fragment_heap(BLOCK_COUNT);
+        bench_encode(bencher, codec, make_sample_data(1024 * 1024));
+    }
+
+    #[bench]
+    fn rustc_and_cbor_encode_big_fragmented(bencher: &mut Bencher) {
+        bench_encode_big_fragmented(bencher, &codec::RustcAndCbor);
+    }
+
+    #[bench]
+    fn serde_and_bincode_encode_big_fragmented(bencher: &mut Bencher) {
+        bench_encode_big_fragmented(bencher, &codec::SerdeAndBincode);
+    }
+
+    #[bench]
+    fn rustc_and_bincode_encode_big_fragmented(bencher: &mut Bencher) {
+        bench_encode_big_fragmented(bencher, &codec::RustcAndBincode);
+    }
+
+    #[bench]
+    fn serde_and_json_encode_big_fragmented(bencher: &mut Bencher) {
+        bench_encode_big_fragmented(bencher, &codec::SerdeAndJson);
+    }
+}
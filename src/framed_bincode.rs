@@ -0,0 +1,118 @@
+use std::mem;
+use byteorder::{BigEndian, ByteOrder};
+use serde::{Deserialize, Serialize};
+use EvalError;
+
+/// An unsigned integer type usable as a big-endian frame length prefix.
+pub trait Prefix {
+    /// The number of bytes the prefix takes.
+    fn width() -> usize;
+    fn max() -> u64;
+    /// Writes `len`, which must be at most `max()`, to the start of `buffer`.
+    fn write(buffer: &mut [u8], len: u64);
+    fn read(buffer: &[u8]) -> u64;
+}
+
+macro_rules! impl_prefix {
+    ($ty:ty, $write:expr, $read:expr) => {
+        impl Prefix for $ty {
+            fn width() -> usize {
+                mem::size_of::<$ty>()
+            }
+
+            fn max() -> u64 {
+                <$ty>::max_value() as u64
+            }
+
+            fn write(buffer: &mut [u8], len: u64) {
+                $write(buffer, len as $ty)
+            }
+
+            fn read(buffer: &[u8]) -> u64 {
+                $read(buffer) as u64
+            }
+        }
+    }
+}
+
+impl_prefix!(u8, |buffer: &mut [u8], len| buffer[0] = len, |buffer: &[u8]| buffer[0]);
+impl_prefix!(u16, BigEndian::write_u16, BigEndian::read_u16);
+impl_prefix!(u32, BigEndian::write_u32, BigEndian::read_u32);
+impl_prefix!(u64, BigEndian::write_u64, BigEndian::read_u64);
+
+/// Encodes `v` with bincode, prefixed by its length as a `P`.  Fails if the length doesn't fit.
+pub fn write_framed<P: Prefix, T: Serialize>(v: &T) -> Result<Vec<u8>, EvalError> {
+    let payload = try!(::serde_and_bincode::encode_result(v));
+    let len = payload.len() as u64;
+    if len > P::max() {
+        return Err(EvalError::FrameTooLong {
+            len: len,
+            max: P::max(),
+        });
+    }
+    let mut bytes = vec![0; P::width()];
+    P::write(&mut bytes, len);
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+/// Decodes one message written by `write_framed::<P>` from the front of `bytes`, advancing it past
+/// the message.
+pub fn read_framed<P: Prefix, T: Deserialize>(bytes: &mut &[u8]) -> Result<T, EvalError> {
+    if bytes.len() < P::width() {
+        return Err(EvalError::TruncatedFrame);
+    }
+    let len = P::read(bytes);
+    let remaining = &bytes[P::width()..];
+    if (remaining.len() as u64) < len {
+        return Err(EvalError::TruncatedFrame);
+    }
+    let (payload, rest) = remaining.split_at(len as usize);
+    let value = try!(::serde_and_bincode::decode_result(payload));
+    *bytes = rest;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_framed, write_framed, Prefix};
+    use {make_sample_data, Document, EvalError};
+
+    fn round_trip<P: Prefix>(size: usize) {
+        let document = make_sample_data(size);
+        let bytes = write_framed::<P, _>(&document).unwrap();
+        assert_eq!(bytes.len(), P::width() + ::serde_and_bincode::encode(&document).len());
+        let mut remaining = &bytes[..];
+        assert_eq!(read_framed::<P, Document>(&mut remaining).unwrap(), document);
+        assert!(remaining.is_empty());
+    }
+
+    fn assert_too_long<P: Prefix>(size: usize) {
+        assert_err!(write_framed::<P, _>(&make_sample_data(size)),
+                    EvalError::FrameTooLong { max, .. } => assert_eq!(max, P::max()));
+    }
+
+    #[test]
+    fn widths() {
+        round_trip::<u8>(0);
+        round_trip::<u16>(1024);
+        round_trip::<u32>(1024 * 1024);
+        round_trip::<u64>(1024 * 1024);
+    }
+
+    #[test]
+    fn too_long() {
+        assert_too_long::<u8>(256);
+        assert_too_long::<u16>(64 * 1024);
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = write_framed::<u32, _>(&make_sample_data(1024)).unwrap();
+        for len in &[0, 3, bytes.len() - 1] {
+            let mut remaining = &bytes[..*len];
+            assert_err!(read_framed::<u32, Document>(&mut remaining), EvalError::TruncatedFrame);
+            assert_eq!(remaining.len(), *len);
+        }
+    }
+}
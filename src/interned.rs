@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use {EvalError, Person};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct InternedPerson {
+    id: u64,
+    name: u32,
+    email: u32,
+}
+
+#[derive(Serialize)]
+struct InternedRef<'a> {
+    strings: Vec<&'a str>,
+    people: Vec<InternedPerson>,
+}
+
+#[derive(Deserialize)]
+struct Interned {
+    strings: Vec<String>,
+    people: Vec<InternedPerson>,
+}
+
+fn intern<'a>(value: &'a str,
+              strings: &mut Vec<&'a str>,
+              indices: &mut HashMap<&'a str, u32>)
+              -> u32 {
+    *indices.entry(value).or_insert_with(|| {
+        strings.push(value);
+        strings.len() as u32 - 1
+    })
+}
+
+/// Encodes `people` with bincode, writing each distinct name or email once in a string table and
+/// each person's name and email as indices into that table.
+pub fn encode_interned(people: &[Person]) -> Vec<u8> {
+    let mut strings = Vec::new();
+    let mut indices = HashMap::new();
+    let mut interned = Vec::with_capacity(people.len());
+    for person in people {
+        interned.push(InternedPerson {
+            id: person.id,
+            name: intern(&person.name, &mut strings, &mut indices),
+            email: intern(&person.email, &mut strings, &mut indices),
+        });
+    }
+    ::serde_and_bincode::encode(&InternedRef {
+        strings: strings,
+        people: interned,
+    })
+}
+
+fn lookup(strings: &[String], index: u32) -> Result<String, EvalError> {
+    match strings.get(index as usize) {
+        Some(value) => Ok(value.clone()),
+        None => Err(EvalError::InvalidStringIndex(index)),
+    }
+}
+
+/// Decodes output of `encode_interned`, giving each person their own copy of each string.
+pub fn decode_interned(bytes: &[u8]) -> Result<Vec<Person>, EvalError> {
+    let interned: Interned = try!(::serde_and_bincode::decode_result(bytes));
+    let mut people = Vec::with_capacity(interned.people.len());
+    for person in interned.people {
+        people.push(Person {
+            id: person.id,
+            name: try!(lookup(&interned.strings, person.name)),
+            email: try!(lookup(&interned.strings, person.email)),
+        });
+    }
+    Ok(people)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_interned, encode_interned, InternedPerson, InternedRef};
+    use test::Bencher;
+    use {EvalError, Person};
+
+    const PERSON_COUNT: usize = 10_000;
+    const UNIQUE_NAMES: usize = 50;
+
+    fn make_repeated_people(count: usize, unique: usize) -> Vec<Person> {
+        (0..count)
+            .map(|i| {
+                Person {
+                    id: i as u64,
+                    name: format!("Person {}", i % unique),
+                    email: format!("person{}@example.com", i % unique),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let people = make_repeated_people(PERSON_COUNT, UNIQUE_NAMES);
+        assert_eq!(decode_interned(&encode_interned(&people)).unwrap(), people);
+        assert_eq!(decode_interned(&encode_interned(&[])).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn invalid_index() {
+        let bytes = ::serde_and_bincode::encode(&InternedRef {
+            strings: vec!["Alice", "alice@example.com"],
+            people: vec![InternedPerson {
+                             id: 1,
+                             name: 0,
+                             email: 2,
+                         }],
+        });
+        assert_err!(decode_interned(&bytes), EvalError::InvalidStringIndex(2));
+    }
+
+    #[test]
+    fn interned_sizes() {
+        let people = make_repeated_people(PERSON_COUNT, UNIQUE_NAMES);
+        let plain = ::serde_and_bincode::encode(&people);
+        let interned = encode_interned(&people);
+        println!("");
+        println!("Size of {} people with {} distinct names, before and after deflate:",
+                 PERSON_COUNT,
+                 UNIQUE_NAMES);
+        println!("    serde_and_bincode: {} bytes, deflated: {} bytes",
+                 plain.len(),
+                 ::compress::deflate_compress(&plain).len());
+        println!("    interned:          {} bytes, deflated: {} bytes",
+                 interned.len(),
+                 ::compress::deflate_compress(&interned).len());
+        // Each person shrinks to 16 bytes: an id and two u32 indices.
+        assert!(interned.len() < plain.len());
+    }
+
+    #[bench]
+    fn serde_and_bincode_encode_repeated_people(bencher: &mut Bencher) {
+        let people = make_repeated_people(PERSON_COUNT, UNIQUE_NAMES);
+        bencher.iter(|| ::serde_and_bincode::encode(&people))
+    }
+
+    #[bench]
+    fn interned_encode_repeated_people(bencher: &mut Bencher) {
+        let people = make_repeated_people(PERSON_COUNT, UNIQUE_NAMES);
+        bencher.iter(|| encode_interned(&people))
+    }
+
+    #[bench]
+    fn serde_and_bincode_decode_repeated_people(bencher: &mut Bencher) {
+        let bytes = ::serde_and_bincode::encode(&make_repeated_people(PERSON_COUNT, UNIQUE_NAMES));
+        bencher.iter(|| ::serde_and_bincode::decode::<Vec<Person>>(&bytes))
+    }
+
+    #[bench]
+    fn interned_decode_repeated_people(bencher: &mut Bencher) {
+        let bytes = encode_interned(&make_repeated_people(PERSON_COUNT, UNIQUE_NAMES));
+        bencher.iter(|| decode_interned(&bytes).unwrap())
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+use rustc_serialize::{Decodable, Decoder, Encodable, Encoder};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+
+const SIGNATURE_LEN: usize = 64;
+
+/// A 64-byte signature.  Neither library implements its traits for arrays longer than 32, so this
+/// is encoded the same way they encode shorter arrays: as a sequence of bytes.
+pub struct Signature(pub [u8; SIGNATURE_LEN]);
+
+impl PartialEq for Signature {
+    fn eq(&self, other: &Signature) -> bool {
+        self.0[..] == other.0[..]
+    }
+}
+
+impl fmt::Debug for Signature {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.0[..].fmt(formatter)
+    }
+}
+
+fn from_vec(bytes: Vec<u8>) -> Option<Signature> {
+    if bytes.len() != SIGNATURE_LEN {
+        return None;
+    }
+    let mut signature = [0; SIGNATURE_LEN];
+    signature.clone_from_slice(&bytes);
+    Some(Signature(signature))
+}
+
+impl Serialize for Signature {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        self.0[..].serialize(serializer)
+    }
+}
+
+impl Deserialize for Signature {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<Signature, D::Error> {
+        let bytes: Vec<u8> = try!(Deserialize::deserialize(deserializer));
+        let len = bytes.len();
+        from_vec(bytes).ok_or_else(|| D::Error::length_mismatch(len))
+    }
+}
+
+impl Encodable for Signature {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), E::Error> {
+        self.0[..].encode(encoder)
+    }
+}
+
+impl Decodable for Signature {
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<Signature, D::Error> {
+        let bytes: Vec<u8> = try!(Decodable::decode(decoder));
+        match from_vec(bytes) {
+            Some(signature) => Ok(signature),
+            None => Err(decoder.error("wrong signature length")),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct Keyed {
+    key: [u8; 32],
+    sig: Signature,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Keyed, Signature, SIGNATURE_LEN};
+
+    fn make_keyed() -> Keyed {
+        let mut key = [0; 32];
+        let mut sig = [0; SIGNATURE_LEN];
+        for (i, byte) in key.iter_mut().chain(sig.iter_mut()).enumerate() {
+            *byte = (i * 7) as u8;
+        }
+        Keyed {
+            key: key,
+            sig: Signature(sig),
+        }
+    }
+
+    #[test]
+    fn sizes_and_round_trips() {
+        println!("");
+        println!("Size of a 32-byte key and 64-byte signature (96 bytes raw):");
+        let keyed = make_keyed();
+        for codec in ::codec::all::<Keyed>() {
+            let bytes = codec.encode(&keyed).unwrap();
+            assert_eq!(codec.decode(&bytes).unwrap(), keyed);
+            println!("    {}: {} bytes", codec.name(), bytes.len());
+        }
+    }
+
+    #[test]
+    fn wrong_signature_length() {
+        let key = make_keyed().key;
+        let full = ::serde_and_bincode::encode(&(&key[..], &[0u8; SIGNATURE_LEN][..]));
+        assert!(::serde_and_bincode::decode_result::<Keyed>(&full).is_ok());
+        let short = ::serde_and_bincode::encode(&(&key[..], &[0u8; SIGNATURE_LEN - 1][..]));
+        assert!(::serde_and_bincode::decode_result::<Keyed>(&short).is_err());
+    }
+}
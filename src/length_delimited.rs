@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use varint;
+use EvalError;
+
+/// Encodes `v` with bincode, prefixed by its length as a base 128 varint.  This is the framing
+/// protobuf (and hence gRPC streaming) uses for delimited messages.
+pub fn encode_length_delimited<T: Serialize>(v: &T) -> Vec<u8> {
+    let payload = ::serde_and_bincode::encode(v);
+    let mut bytes = Vec::with_capacity(payload.len() + 10);
+    varint::write_u64(&mut bytes, payload.len() as u64);
+    bytes.extend_from_slice(&payload);
+    bytes
+}
+
+/// Decodes one message written by `encode_length_delimited` from the front of `bytes`, advancing
+/// it past the message.
+pub fn decode_length_delimited<T: Deserialize>(bytes: &mut &[u8]) -> Result<T, EvalError> {
+    let (len, consumed) = match varint::read_u64(bytes) {
+        Ok(result) => result,
+        Err(EvalError::TruncatedVarint) => return Err(EvalError::TruncatedFrame),
+        Err(_) => return Err(EvalError::InvalidFrameLength),
+    };
+    let remaining = &bytes[consumed..];
+    if (remaining.len() as u64) < len {
+        return Err(EvalError::TruncatedFrame);
+    }
+    let (payload, rest) = remaining.split_at(len as usize);
+    let value = try!(::serde_and_bincode::decode_result(payload));
+    *bytes = rest;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_length_delimited, encode_length_delimited};
+    use {make_sample_data, Document, EvalError};
+
+    fn make_stream(documents: &[Document]) -> Vec<u8> {
+        let mut stream = Vec::new();
+        for document in documents {
+            stream.extend_from_slice(&encode_length_delimited(document));
+        }
+        stream
+    }
+
+    #[test]
+    fn stream() {
+        let documents: Vec<_> = (0..5).map(|i| make_sample_data(i * 100)).collect();
+        let stream = make_stream(&documents);
+        let mut remaining = &stream[..];
+        let mut decoded = Vec::new();
+        while !remaining.is_empty() {
+            decoded.push(decode_length_delimited::<Document>(&mut remaining).unwrap());
+        }
+        assert_eq!(decoded, documents);
+    }
+
+    #[test]
+    fn truncated_mid_stream() {
+        let documents: Vec<_> = (0..3).map(|_| make_sample_data(1024)).collect();
+        let stream = make_stream(&documents);
+        let mut remaining = &stream[..stream.len() - 10];
+        assert!(decode_length_delimited::<Document>(&mut remaining).is_ok());
+        assert!(decode_length_delimited::<Document>(&mut remaining).is_ok());
+        let before = remaining.len();
+        assert_err!(decode_length_delimited::<Document>(&mut remaining), EvalError::TruncatedFrame);
+        assert_eq!(remaining.len(), before);
+    }
+
+    #[test]
+    fn invalid_length() {
+        let mut bytes = &[0xff; 16][..];
+        assert_err!(decode_length_delimited::<Document>(&mut bytes), EvalError::InvalidFrameLength);
+    }
+}
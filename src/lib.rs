@@ -1,24 +1,172 @@
-#![cfg(test)]
 #![feature(test, custom_derive, plugin)]
 #![plugin(serde_macros)]
+#![cfg_attr(feature = "system_alloc", feature(alloc_system))]
+#![cfg_attr(all(feature = "cycles", target_arch = "x86_64"), feature(asm))]
+
+#[cfg(feature = "system_alloc")]
+extern crate alloc_system;
+#[cfg(feature = "counting_alloc")]
+extern crate counting_allocator;
 
 extern crate rustc_serialize;
 extern crate serde;
+extern crate serde_json;
 
 extern crate cbor;
 extern crate bincode;
 
+extern crate byteorder;
+extern crate bytes;
+
+extern crate flate2;
+extern crate smallvec;
+
 extern crate rand;
 extern crate test;
 
-#[derive(Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+// Declared ahead of the other modules so that its macros are in scope for all of them.
+#[cfg(test)]
+#[macro_use]
+mod bench;
+
+#[cfg(test)]
+mod adaptive;
+#[cfg(test)]
+mod arbitrary;
+#[cfg(test)]
+mod asymmetry;
+#[cfg(test)]
+mod base64;
+#[cfg(test)]
+mod borrow_map;
+#[cfg(test)]
+mod byte_content;
+#[cfg(test)]
+mod bytes_crate;
+mod cbor_canonical;
+#[cfg(test)]
+mod char_record;
+#[cfg(test)]
+mod chunked_document;
+#[cfg(test)]
+mod chunking;
+pub mod codec;
+#[cfg(test)]
+mod cold_cache;
+#[cfg(test)]
+mod columnar;
+#[cfg(test)]
+mod content_hash;
+#[cfg(test)]
+mod content_len;
+#[cfg(test)]
+mod control_msg;
+#[cfg(test)]
+mod crc32;
+#[cfg(all(test, feature = "cycles", target_arch = "x86_64"))]
+mod cycles;
+#[cfg(test)]
+mod deduped;
+#[cfg(test)]
+mod diff;
+#[cfg(test)]
+mod document_ref;
+#[cfg(test)]
+mod drop_cost;
+#[cfg(test)]
+mod empty_collections;
+#[cfg(test)]
+mod endpoint;
+#[cfg(test)]
+mod envelope;
+mod error;
+#[cfg(test)]
+mod field_order;
+#[cfg(test)]
+mod flags;
+#[cfg(test)]
+mod fragmented_heap;
+#[cfg(test)]
+mod framed_bincode;
+#[cfg(test)]
+mod interned;
+#[cfg(test)]
+mod keyed;
+#[cfg(test)]
+mod length_delimited;
+#[cfg(test)]
+mod manual_person;
+#[cfg(test)]
+mod many_authors;
+#[cfg(test)]
+mod map_keys;
+#[cfg(test)]
+mod max_len;
+#[cfg(test)]
+mod measurement;
+#[cfg(test)]
+mod nested_doc;
+#[cfg(test)]
+mod optionally;
+#[cfg(test)]
+mod person_ref;
+#[cfg(test)]
+mod raw_json;
+#[cfg(test)]
+mod recommend;
+#[cfg(test)]
+mod response;
+#[cfg(test)]
+mod resumable;
+#[cfg(test)]
+mod reused_content;
+#[cfg(test)]
+mod sha256;
+#[cfg(test)]
+mod shared_authors;
+#[cfg(test)]
+mod short_names;
+#[cfg(test)]
+mod size_hint;
+#[cfg(test)]
+mod skipped_field;
+#[cfg(test)]
+mod small_authors;
+#[cfg(test)]
+mod snapshot;
+#[cfg(test)]
+mod sorted_map;
+#[cfg(test)]
+mod split;
+#[cfg(test)]
+mod tagged_enum;
+#[cfg(test)]
+mod timestamped;
+#[cfg(all(test, feature = "unsafe"))]
+mod unchecked_utf8;
+#[cfg(test)]
+mod validate;
+#[cfg(test)]
+mod varint;
+#[cfg(test)]
+mod versioned;
+#[cfg(test)]
+mod warm_pool;
+#[cfg(test)]
+mod wide_struct;
+#[cfg(test)]
+mod zero_sized;
+
+pub use error::EvalError;
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
 pub struct Person {
     id: u64,
     name: String,
     email: String,
 }
 
-#[derive(Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
 pub struct Document {
     id: u64,
     name: String,
@@ -26,9 +174,36 @@ pub struct Document {
     content: Vec<u8>,
 }
 
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct SignedRecord {
+    a: i64,
+    b: i32,
+    c: i16,
+}
+
+#[cfg(test)]
 fn make_sample_data(size: usize) -> Document {
-    use rand::{thread_rng, Rng};
+    make_sample_data_with_rng(size, &mut ::rand::thread_rng())
+}
+
+/// Like `make_sample_data`, but with content which is all zeros.
+#[cfg(test)]
+fn make_compressible_sample_data(size: usize) -> Document {
+    let mut document = make_sample_data(0);
+    document.content = vec![0; size];
+    document
+}
+
+#[cfg(test)]
+fn make_seeded_sample_data(size: usize) -> Document {
+    use rand::{SeedableRng, XorShiftRng};
 
+    let mut rng = XorShiftRng::from_seed([0x193a6754, 0xa8a7d469, 0x97830e05, 0x113ba7bb]);
+    make_sample_data_with_rng(size, &mut rng)
+}
+
+#[cfg(test)]
+fn make_sample_data_with_rng<R: ::rand::Rng>(size: usize, rng: &mut R) -> Document {
     let alice = Person {
         id: 1,
         name: "Alice".to_owned(),
@@ -41,7 +216,6 @@ fn make_sample_data(size: usize) -> Document {
         email: "bob@example.com".to_owned(),
     };
 
-    let mut rng = thread_rng();
     let mut content =vec![0u8; size];
     for i in content.iter_mut() {
         *i = rng.gen();
@@ -54,55 +228,266 @@ fn make_sample_data(size: usize) -> Document {
     }
 }
 
+#[cfg(test)]
+fn make_people(count: usize) -> Vec<Person> {
+    (0..count)
+        .map(|i| {
+            Person {
+                id: i as u64,
+                name: format!("Person {}", i),
+                email: format!("person{}@example.com", i),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+fn make_signed_records() -> Vec<SignedRecord> {
+    use std::{i16, i32, i64};
+
+    vec![SignedRecord { a: i64::MIN, b: i32::MIN, c: i16::MIN },
+         SignedRecord { a: i64::MIN + 1, b: i32::MIN + 1, c: i16::MIN + 1 },
+         SignedRecord { a: -4294967296, b: -65536, c: -256 },
+         SignedRecord { a: -24, b: -25, c: -1 },
+         SignedRecord { a: 0, b: 0, c: 0 },
+         SignedRecord { a: 4294967296, b: 65536, c: 256 },
+         SignedRecord { a: i64::MAX, b: i32::MAX, c: i16::MAX }]
+}
+
 mod rustc_and_cbor {
+    #[cfg(test)]
+    use std::io::Read;
     use rustc_serialize::{Decodable, Encodable};
-    use cbor::{Decoder, Encoder};
+    use cbor::{CborError, Decoder, Encoder};
+    use EvalError;
 
-    pub fn encode<T: Encodable>(v: T) -> Vec<u8> {
-        let mut encoder = Encoder::from_memory();
-        encoder.encode(&[v]).unwrap();
-        encoder.as_bytes().to_vec()
+    #[cfg(test)]
+    pub fn encode<T: Encodable>(v: &T) -> Vec<u8> {
+        encode_result(v).unwrap()
     }
 
+    #[cfg(test)]
     pub fn decode<T: Decodable>(bytes: &[u8]) -> T {
+        decode_result(bytes).unwrap()
+    }
+
+    pub fn encode_result<T: Encodable>(v: &T) -> Result<Vec<u8>, EvalError> {
+        let mut encoder = Encoder::from_memory();
+        try!(encoder.encode(&[v]));
+        Ok(encoder.as_bytes().to_vec())
+    }
+
+    pub fn decode_result<T: Decodable>(bytes: &[u8]) -> Result<T, EvalError> {
         let mut decoder = Decoder::from_bytes(bytes);
+        match decoder.decode().next() {
+            Some(result) => Ok(try!(result)),
+            None => Err(EvalError::Cbor(CborError::UnexpectedEOF)),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn decode_from_reader<R: Read, T: Decodable>(reader: R) -> T {
+        let mut decoder = Decoder::from_reader(reader);
         decoder.decode().next().unwrap().unwrap()
     }
 }
 
 mod serde_and_bincode {
+    #[cfg(test)]
+    use std::io::{self, Write};
     use serde::{Deserialize, Serialize};
+    #[cfg(test)]
+    use smallvec::SmallVec;
     use bincode::SizeLimit;
     use bincode::serde;
+    use EvalError;
 
-    pub fn encode<T: Serialize>(v: T) -> Vec<u8> {
-        serde::serialize(&v, SizeLimit::Infinite).unwrap()
+    #[cfg(test)]
+    pub fn encode<T: Serialize>(v: &T) -> Vec<u8> {
+        encode_result(v).unwrap()
     }
 
+    #[cfg(test)]
     pub fn decode<T: Deserialize>(bytes: &[u8]) -> T {
-        serde::deserialize(bytes).unwrap()
+        decode_result(bytes).unwrap()
+    }
+
+    #[cfg(test)]
+    pub fn encode_into<W: Write, T: Serialize>(writer: &mut W, v: &T) {
+        serde::serialize_into(writer, v, SizeLimit::Infinite).unwrap()
+    }
+
+    /// Encodes into a caller-provided buffer, returning the number of bytes written.  This doesn't
+    /// allocate, so the buffer can live on the stack.
+    #[cfg(test)]
+    pub fn encode_into_slice<T: Serialize>(buffer: &mut [u8], v: &T) -> Result<usize, EvalError> {
+        let capacity = buffer.len();
+        let mut remaining = buffer;
+        try!(serde::serialize_into(&mut remaining, v, SizeLimit::Infinite));
+        Ok(capacity - remaining.len())
+    }
+
+    #[cfg(test)]
+    struct SmallVecWriter<'a>(&'a mut SmallVec<[u8; 256]>);
+
+    #[cfg(test)]
+    impl<'a> Write for SmallVecWriter<'a> {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            // `extend` alone grows to exactly the new length, and bincode writes a byte at a time,
+            // so every write after spilling would reallocate.  `reserve` rounds up to a power of
+            // two.
+            self.0.reserve(bytes.len());
+            self.0.extend(bytes.iter().cloned());
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Encodes into a buffer which only moves to the heap if the output exceeds 256 bytes.
+    #[cfg(test)]
+    pub fn encode_smallvec<T: Serialize>(v: &T) -> SmallVec<[u8; 256]> {
+        let mut buffer = SmallVec::new();
+        encode_into(&mut SmallVecWriter(&mut buffer), v);
+        buffer
+    }
+
+    pub fn encode_result<T: Serialize>(v: &T) -> Result<Vec<u8>, EvalError> {
+        Ok(try!(serde::serialize(v, SizeLimit::Infinite)))
+    }
+
+    pub fn decode_result<T: Deserialize>(bytes: &[u8]) -> Result<T, EvalError> {
+        Ok(try!(serde::deserialize(bytes)))
     }
 }
 
 mod rustc_and_bincode {
+    #[cfg(test)]
+    use std::io::Write;
     use rustc_serialize::{Decodable, Encodable};
     use bincode::SizeLimit;
     use bincode::rustc_serialize;
+    use EvalError;
 
-    pub fn encode<T: Encodable>(v: T) -> Vec<u8> {
-        rustc_serialize::encode(&v, SizeLimit::Infinite).unwrap()
+    #[cfg(test)]
+    pub fn encode<T: Encodable>(v: &T) -> Vec<u8> {
+        encode_result(v).unwrap()
     }
 
+    #[cfg(test)]
     pub fn decode<T: Decodable>(bytes: &[u8]) -> T {
-        rustc_serialize::decode(bytes).unwrap()
+        decode_result(bytes).unwrap()
+    }
+
+    #[cfg(test)]
+    pub fn encode_into<W: Write, T: Encodable>(writer: &mut W, v: &T) {
+        rustc_serialize::encode_into(v, writer, SizeLimit::Infinite).unwrap()
+    }
+
+    pub fn encode_result<T: Encodable>(v: &T) -> Result<Vec<u8>, EvalError> {
+        Ok(try!(rustc_serialize::encode(v, SizeLimit::Infinite)))
+    }
+
+    pub fn decode_result<T: Decodable>(bytes: &[u8]) -> Result<T, EvalError> {
+        Ok(try!(rustc_serialize::decode(bytes)))
+    }
+}
+
+mod serde_and_json {
+    use std::str;
+    use serde::{Deserialize, Serialize};
+    use serde_json;
+    use EvalError;
+
+    #[cfg(test)]
+    pub fn encode<T: Serialize>(v: &T) -> Vec<u8> {
+        encode_result(v).unwrap()
+    }
+
+    #[cfg(test)]
+    pub fn decode<T: Deserialize>(bytes: &[u8]) -> T {
+        decode_result(bytes).unwrap()
+    }
+
+    /// Encodes into `buffer`, replacing its contents but keeping its allocation.
+    #[cfg(test)]
+    pub fn encode_into<T: Serialize>(buffer: &mut Vec<u8>, v: &T) {
+        buffer.clear();
+        serde_json::to_writer(buffer, v).unwrap()
+    }
+
+    pub fn encode_result<T: Serialize>(v: &T) -> Result<Vec<u8>, EvalError> {
+        Ok(try!(serde_json::to_vec(v)))
+    }
+
+    // serde_json 0.6 panics on a string which isn't valid UTF-8, so the whole input is checked
+    // up front.  It also panics in debug builds on the valid number `-0`, where negating it
+    // overflows; that's left as a known limitation, pinned by `measurement`'s tests.
+    pub fn decode_result<T: Deserialize>(bytes: &[u8]) -> Result<T, EvalError> {
+        let text = try!(str::from_utf8(bytes));
+        Ok(try!(serde_json::from_str(text)))
+    }
+}
+
+#[cfg(test)]
+mod compress {
+    use std::io::{Read, Write};
+    use flate2::Compression;
+    use flate2::read::{DeflateDecoder, GzDecoder};
+    use flate2::write::{DeflateEncoder, GzEncoder};
+    use EvalError;
+
+    pub fn gzip_compress(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::Default);
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    pub fn gzip_decompress(bytes: &[u8]) -> Vec<u8> {
+        let mut decoder = GzDecoder::new(bytes).unwrap();
+        let mut result = Vec::new();
+        decoder.read_to_end(&mut result).unwrap();
+        result
+    }
+
+    pub fn deflate_compress(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::Default);
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    pub fn deflate_decompress(bytes: &[u8]) -> Vec<u8> {
+        deflate_decompress_result(bytes, u64::max_value()).unwrap()
+    }
+
+    /// Inflates `bytes`, failing rather than producing more than `max_len` bytes.
+    pub fn deflate_decompress_result(bytes: &[u8], max_len: u64) -> Result<Vec<u8>, EvalError> {
+        let mut decoder = DeflateDecoder::new(bytes);
+        let mut result = Vec::new();
+        try!((&mut decoder).take(max_len).read_to_end(&mut result).map_err(EvalError::Decompress));
+        // Reaching the limit is only an error if there's more to come.
+        if result.len() as u64 == max_len &&
+           try!(decoder.read(&mut [0]).map_err(EvalError::Decompress)) > 0 {
+            return Err(EvalError::DecompressedTooLong(max_len));
+        }
+        Ok(result)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{make_sample_data, Document};
+    use super::{make_compressible_sample_data, make_people, make_sample_data,
+                make_seeded_sample_data, make_signed_records, Document, EvalError, Person};
+    use rustc_serialize::{Decodable, Encodable};
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
     use std::fmt;
-    use test::Bencher;
+    use std::io::{self, Cursor};
+    use codec;
+    use control_msg::make_control_msg;
+    use test::{black_box, Bencher};
 
     enum Option {
         RustcAndCbor,
@@ -110,6 +495,20 @@ mod tests {
         RustcAndBincode,
     }
 
+    const ALL_OPTIONS: &'static [Option] = &[Option::RustcAndCbor,
+                                              Option::SerdeAndBincode,
+                                              Option::RustcAndBincode];
+
+    impl Option {
+        fn name(&self) -> &'static str {
+            match *self {
+                Option::RustcAndCbor => "rustc_and_cbor",
+                Option::SerdeAndBincode => "serde_and_bincode",
+                Option::RustcAndBincode => "rustc_and_bincode",
+            }
+        }
+    }
+
     impl fmt::Debug for Option {
         fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
             match *self {
@@ -120,6 +519,18 @@ mod tests {
         }
     }
 
+    // Fails to compile if a derive which some codec or test relies on is removed.
+    fn assert_codec_traits<T>()
+        where T: Encodable + Decodable + Serialize + Deserialize + PartialEq + fmt::Debug
+    {
+    }
+
+    #[test]
+    fn derived_traits() {
+        assert_codec_traits::<Person>();
+        assert_codec_traits::<Document>();
+    }
+
     fn run_sizes(option: Option, small: &Document, big: &Document) {
         println!("{:?}", option);
         match option {
@@ -138,6 +549,199 @@ mod tests {
         }
     }
 
+    fn encode<T: Encodable + Serialize>(option: &Option, value: &T) -> Vec<u8> {
+        match *option {
+            Option::RustcAndCbor => ::rustc_and_cbor::encode(value),
+            Option::SerdeAndBincode => ::serde_and_bincode::encode(value),
+            Option::RustcAndBincode => ::rustc_and_bincode::encode(value),
+        }
+    }
+
+    fn round_trip<T>(option: &Option, value: &T) -> Result<T, EvalError>
+        where T: Encodable + Decodable + Serialize + Deserialize
+    {
+        match *option {
+            Option::RustcAndCbor => {
+                let bytes = try!(::rustc_and_cbor::encode_result(value));
+                ::rustc_and_cbor::decode_result(&bytes)
+            }
+            Option::SerdeAndBincode => {
+                let bytes = try!(::serde_and_bincode::encode_result(value));
+                ::serde_and_bincode::decode_result(&bytes)
+            }
+            Option::RustcAndBincode => {
+                let bytes = try!(::rustc_and_bincode::encode_result(value));
+                ::rustc_and_bincode::decode_result(&bytes)
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let small = make_sample_data(0);
+        let big = make_sample_data(1024 * 1024);
+        for option in ALL_OPTIONS {
+            for document in &[&small, &big] {
+                match round_trip(option, *document) {
+                    Ok(decoded) => {
+                        let diffs = ::diff::compare_documents(document, &decoded);
+                        assert!(diffs.is_empty(), "{:?}: {}", option, ::diff::describe(&diffs));
+                    }
+                    Err(error) => panic!("{:?}: {}", option, error),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn signed_records() {
+        println!("");
+        println!("Size of signed records:");
+        for option in ALL_OPTIONS {
+            println!("{:?}", option);
+            for record in make_signed_records() {
+                match round_trip(option, &record) {
+                    Ok(decoded) => assert_eq!(decoded, record),
+                    Err(error) => panic!("{:?}: {:?}: {}", option, record, error),
+                }
+                println!("    {:?}: {} bytes", record, encode(option, &record).len());
+            }
+        }
+    }
+
+    #[test]
+    fn snapshots() {
+        let document = make_seeded_sample_data(64);
+        for option in ALL_OPTIONS {
+            ::snapshot::check(option.name(), &encode(option, &document));
+        }
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+    struct Unit;
+
+    struct Capability {
+        construct: &'static str,
+        supported: Vec<bool>,
+    }
+
+    fn capability<T>(construct: &'static str, value: &T) -> Capability
+        where T: Encodable + Decodable + Serialize + Deserialize + PartialEq
+    {
+        let supported = ALL_OPTIONS.iter()
+                                   .map(|option| {
+                                       match round_trip(option, value) {
+                                           Ok(decoded) => decoded == *value,
+                                           Err(_) => false,
+                                       }
+                                   })
+                                   .collect();
+        Capability {
+            construct: construct,
+            supported: supported,
+        }
+    }
+
+    #[test]
+    fn capabilities() {
+        let mut integer_keys = HashMap::new();
+        integer_keys.insert(1u64, 10u64);
+        integer_keys.insert(2u64, 20u64);
+
+        let matrix = vec![capability("map with integer keys", &integer_keys),
+                          capability("top-level Vec", &vec![1u64, 2, 3]),
+                          capability("unit struct", &Unit),
+                          capability("Some(None)", &Some(None::<u64>)),
+                          capability("empty string", &String::new())];
+
+        println!("");
+        println!("Round-trip capabilities:");
+        print!("    {:24}", "");
+        for option in ALL_OPTIONS {
+            print!("{:20}", option.name());
+        }
+        println!("");
+        for capability in &matrix {
+            print!("    {:24}", capability.construct);
+            for &supported in &capability.supported {
+                print!("{:20}", if supported { "yes" } else { "no" });
+            }
+            println!("");
+        }
+    }
+
+    fn run_compressed_sizes(option: Option, small: &Document, big: &Document) {
+        println!("{:?}", option);
+        for &(label, document) in &[("small:", small), ("big:  ", big)] {
+            let bytes = encode(&option, document);
+            let gzip = ::compress::gzip_compress(&bytes);
+            let deflate = ::compress::deflate_compress(&bytes);
+            assert_eq!(::compress::gzip_decompress(&gzip), bytes);
+            assert_eq!(::compress::deflate_decompress(&deflate), bytes);
+            assert!(deflate.len() < gzip.len());
+            println!("    {} {} bytes, gzip: {} bytes, deflate: {} bytes",
+                     label,
+                     bytes.len(),
+                     gzip.len(),
+                     deflate.len());
+        }
+    }
+
+    #[test]
+    fn compressed_sizes() {
+        println!("");
+        println!("Size after serialization and compression:");
+        let small = make_sample_data(0);
+        let big = make_sample_data(1024 * 1024);
+        run_compressed_sizes(Option::RustcAndCbor, &small, &big);
+        run_compressed_sizes(Option::SerdeAndBincode, &small, &big);
+        run_compressed_sizes(Option::RustcAndBincode, &small, &big);
+    }
+
+    #[test]
+    fn compress_roundtrip() {
+        let compressors: [(&str, fn(&[u8]) -> Vec<u8>, fn(&[u8]) -> Vec<u8>); 2] =
+            [("gzip", ::compress::gzip_compress, ::compress::gzip_decompress),
+             ("deflate", ::compress::deflate_compress, ::compress::deflate_decompress)];
+        let document = make_sample_data(1024 * 1024);
+        for codec in codec::all::<Document>() {
+            let bytes = codec.encode(&document).unwrap();
+            for &(name, compress, decompress) in &compressors {
+                let decompressed = decompress(&compress(&bytes));
+                let decoded = codec.decode(&decompressed).unwrap();
+                let diffs = ::diff::compare_documents(&document, &decoded);
+                assert!(diffs.is_empty(),
+                        "{} with {}: {}",
+                        codec.name(),
+                        name,
+                        ::diff::describe(&diffs));
+            }
+        }
+    }
+
+    #[test]
+    fn compressed_sizes_by_content() {
+        println!("");
+        println!("Size of the big document after serialization and compression:");
+        println!("{:?}", Option::SerdeAndBincode);
+        let random = ::serde_and_bincode::encode(&make_sample_data(1024 * 1024));
+        let zeros = ::serde_and_bincode::encode(&make_compressible_sample_data(1024 * 1024));
+        let random_gzip = ::compress::gzip_compress(&random).len();
+        let zeros_gzip = ::compress::gzip_compress(&zeros).len();
+        assert!(random_gzip > random.len() * 9 / 10);
+        assert!(zeros_gzip < zeros.len() / 100);
+        println!("    random content: {} bytes, gzip: {} bytes", random.len(), random_gzip);
+        println!("    zero content:   {} bytes, gzip: {} bytes", zeros.len(), zeros_gzip);
+    }
+
+    #[test]
+    fn json_encode_into() {
+        let document = make_sample_data(1024);
+        let mut buffer = b"stale".to_vec();
+        ::serde_and_json::encode_into(&mut buffer, &document);
+        assert_eq!(buffer, ::serde_and_json::encode(&document));
+    }
+
     #[test]
     fn sizes() {
         println!("");
@@ -149,91 +753,217 @@ mod tests {
         run_sizes(Option::RustcAndBincode, &small, &big);
     }
 
-    fn bench_encode(bencher: &mut Bencher, option: Option, size: usize) {
-        let document = make_sample_data(size);
+    fn strip_email_domains(people: &[Person], domain: &str) -> Vec<Person> {
+        people.iter()
+              .map(|person| {
+                  Person {
+                      id: person.id,
+                      name: person.name.clone(),
+                      email: person.email.trim_right_matches(domain).to_owned(),
+                  }
+              })
+              .collect()
+    }
 
-        match option {
-            Option::RustcAndCbor => bencher.iter(|| ::rustc_and_cbor::encode(&document)),
-            Option::SerdeAndBincode => bencher.iter(|| ::serde_and_bincode::encode(&document)),
-            Option::RustcAndBincode => bencher.iter(|| ::rustc_and_bincode::encode(&document)),
+    fn run_repeated_email_domain(option: Option, domain: &str) {
+        println!("{:?}", option);
+        for &count in &[100, 200, 400] {
+            let people = make_people(count);
+            let with_domain = encode(&option, &people).len();
+            let without_domain = encode(&option, &strip_email_domains(&people, domain)).len();
+            // Every person pays for the whole shared suffix, i.e. the codec doesn't intern it.
+            assert_eq!(with_domain - without_domain, count * domain.len());
+            println!("    {} people: {} bytes, {:.2} bytes per person",
+                     count,
+                     with_domain,
+                     with_domain as f64 / count as f64);
         }
     }
 
-    fn bench_decode(bencher: &mut Bencher, option: Option, size: usize) {
-        let document = make_sample_data(size);
-        match option {
-            Option::RustcAndCbor => {
-                let bytes = ::rustc_and_cbor::encode(&document);
-                bencher.iter(|| ::rustc_and_cbor::decode::<Document>(&bytes))
-            }
-            Option::SerdeAndBincode => {
-                let bytes = ::serde_and_bincode::encode(&document);
-                bencher.iter(|| ::serde_and_bincode::decode::<Document>(&bytes))
-            }
-            Option::RustcAndBincode => {
-                let bytes = ::rustc_and_bincode::encode(&document);
-                bencher.iter(|| ::rustc_and_bincode::decode::<Document>(&bytes))
-            }
-        }
+    #[test]
+    fn repeated_email_domain() {
+        println!("");
+        println!("Size of people sharing an email domain:");
+        run_repeated_email_domain(Option::RustcAndCbor, "@example.com");
+        run_repeated_email_domain(Option::SerdeAndBincode, "@example.com");
+        run_repeated_email_domain(Option::RustcAndBincode, "@example.com");
+    }
+
+    #[test]
+    fn stack_encode() {
+        let person = make_people(1).pop().unwrap();
+        let mut buffer = [0u8; 64];
+        let len = ::serde_and_bincode::encode_into_slice(&mut buffer, &person).unwrap();
+        assert_eq!(&buffer[..len], &::serde_and_bincode::encode(&person)[..]);
+        assert!(::serde_and_bincode::encode_into_slice(&mut buffer[..len - 1], &person).is_err());
+    }
+
+    #[test]
+    fn smallvec_encode() {
+        let message = make_control_msg(1000);
+        let bytes = ::serde_and_bincode::encode_smallvec(&message);
+        assert!(!bytes.spilled());
+        assert_eq!(&bytes[..], &::serde_and_bincode::encode(&message)[..]);
+
+        let document = make_sample_data(1024);
+        let bytes = ::serde_and_bincode::encode_smallvec(&document);
+        assert!(bytes.spilled());
+        assert_eq!(&bytes[..], &::serde_and_bincode::encode(&document)[..]);
+    }
+
+    #[cfg(feature = "counting_alloc")]
+    #[test]
+    fn small_encode_allocs() {
+        use counting_allocator::allocations;
+        let person = make_people(1).pop().unwrap();
+        let mut buffer = [0u8; 64];
+        let before = allocations();
+        let len = ::serde_and_bincode::encode_into_slice(&mut buffer, &person).unwrap();
+        assert_eq!(allocations(), before);
+        // Make sure the counting allocator is really in use.
+        assert_eq!(::serde_and_bincode::encode(&person).len(), len);
+        assert!(allocations() > before);
+    }
+
+    #[cfg(feature = "counting_alloc")]
+    #[test]
+    fn smallvec_encode_allocs() {
+        use counting_allocator::allocations;
+        let message = make_control_msg(1000);
+        let before = allocations();
+        ::serde_and_bincode::encode_smallvec(&message);
+        assert_eq!(allocations(), before);
     }
 
     #[bench]
-    fn rustc_and_cbor_encode_small(bencher: &mut Bencher) {
-        bench_encode(bencher, Option::RustcAndCbor, 0);
+    fn serde_and_bincode_encode_control_msg_vec(bencher: &mut Bencher) {
+        let message = make_control_msg(1000);
+        bencher.iter(|| ::serde_and_bincode::encode(black_box(&message)))
     }
 
     #[bench]
-    fn serde_and_bincode_encode_small(bencher: &mut Bencher) {
-        bench_encode(bencher, Option::SerdeAndBincode, 0);
+    fn serde_and_bincode_encode_control_msg_smallvec(bencher: &mut Bencher) {
+        let message = make_control_msg(1000);
+        bencher.iter(|| ::serde_and_bincode::encode_smallvec(black_box(&message)))
+    }
+
+    bench_all! {
+        codec::RustcAndCbor => {
+            make_sample_data(0) => rustc_and_cbor_encode_small, rustc_and_cbor_decode_small;
+            make_sample_data(1024 * 1024) => rustc_and_cbor_encode_big, rustc_and_cbor_decode_big;
+        }
+        codec::SerdeAndBincode => {
+            make_sample_data(0) => serde_and_bincode_encode_small, serde_and_bincode_decode_small;
+            make_sample_data(1024 * 1024) => serde_and_bincode_encode_big,
+                                             serde_and_bincode_decode_big;
+        }
+        codec::RustcAndBincode => {
+            make_sample_data(0) => rustc_and_bincode_encode_small, rustc_and_bincode_decode_small;
+            make_sample_data(1024 * 1024) => rustc_and_bincode_encode_big,
+                                             rustc_and_bincode_decode_big;
+        }
+        codec::SerdeAndJson => {
+            make_sample_data(0) => serde_and_json_encode_small, serde_and_json_decode_small;
+            make_sample_data(1024 * 1024) => serde_and_json_encode_big, serde_and_json_decode_big;
+        }
+    }
+
+    fn encode_moved(document: Document) -> (Vec<u8>, Document) {
+        let bytes = ::serde_and_bincode::encode(&document);
+        (bytes, document)
+    }
+
+    fn bench_encode_moved(bencher: &mut Bencher, size: usize) {
+        let mut document = Some(make_sample_data(size));
+        bencher.iter(|| {
+            let (bytes, returned) = encode_moved(document.take().unwrap());
+            document = Some(returned);
+            bytes
+        });
     }
 
     #[bench]
-    fn rustc_and_bincode_encode_small(bencher: &mut Bencher) {
-        bench_encode(bencher, Option::RustcAndBincode, 0);
+    fn serde_and_bincode_encode_big_moved(bencher: &mut Bencher) {
+        bench_encode_moved(bencher, 1024 * 1024);
     }
 
     #[bench]
-    fn rustc_and_cbor_encode_big(bencher: &mut Bencher) {
-        bench_encode(bencher, Option::RustcAndCbor, 1024 * 1024);
+    fn serde_and_json_encode_big_into_reused(bencher: &mut Bencher) {
+        let document = make_sample_data(1024 * 1024);
+        let mut buffer = Vec::new();
+        ::serde_and_json::encode_into(&mut buffer, &document);
+        bencher.iter(|| ::serde_and_json::encode_into(&mut buffer, &document))
     }
 
+    // Writing into a sink leaves only the cost of walking the document and formatting it; the
+    // difference from the plain encode benches is the cost of growing and filling the `Vec`.
     #[bench]
-    fn serde_and_bincode_encode_big(bencher: &mut Bencher) {
-        bench_encode(bencher, Option::SerdeAndBincode, 1024 * 1024);
+    fn serde_and_bincode_encode_big_into_sink(bencher: &mut Bencher) {
+        let document = make_sample_data(1024 * 1024);
+        bencher.iter(|| ::serde_and_bincode::encode_into(&mut io::sink(), &document))
     }
 
     #[bench]
-    fn rustc_and_bincode_encode_big(bencher: &mut Bencher) {
-        bench_encode(bencher, Option::RustcAndBincode, 1024 * 1024);
+    fn rustc_and_bincode_encode_big_into_sink(bencher: &mut Bencher) {
+        let document = make_sample_data(1024 * 1024);
+        bencher.iter(|| ::rustc_and_bincode::encode_into(&mut io::sink(), &document))
     }
 
+    // The floor for encoding the big content: a single copy into a new `Vec`.
     #[bench]
-    fn rustc_and_cbor_decode_small(bencher: &mut Bencher) {
-        bench_decode(bencher, Option::RustcAndCbor, 0);
+    fn extend_from_slice_big_content(bencher: &mut Bencher) {
+        let content = make_sample_data(1024 * 1024).content;
+        bencher.iter(|| {
+            let mut bytes = Vec::new();
+            bytes.extend_from_slice(&content);
+            bytes
+        })
     }
 
     #[bench]
-    fn serde_and_bincode_decode_small(bencher: &mut Bencher) {
-        bench_decode(bencher, Option::SerdeAndBincode, 0);
+    fn serde_and_bincode_encode_big_content_only(bencher: &mut Bencher) {
+        let content = make_sample_data(1024 * 1024).content;
+        bencher.iter(|| ::serde_and_bincode::encode(&content))
+    }
+
+    #[test]
+    fn seeded_sample_data_is_deterministic() {
+        let first = make_seeded_sample_data(1024 * 1024);
+        assert_eq!(make_seeded_sample_data(1024 * 1024), first);
+        assert!(make_sample_data(1024 * 1024) != first);
+    }
+
+    // Generation is left out of every timed region; these give the cost to subtract if it ever
+    // leaks in.
+    #[bench]
+    fn make_sample_data_big(bencher: &mut Bencher) {
+        bencher.iter(|| make_sample_data(1024 * 1024))
     }
 
     #[bench]
-    fn rustc_and_bincode_decode_small(bencher: &mut Bencher) {
-        bench_decode(bencher, Option::RustcAndBincode, 0);
+    fn make_seeded_sample_data_big(bencher: &mut Bencher) {
+        bencher.iter(|| make_seeded_sample_data(1024 * 1024))
+    }
+
+    fn bench_encode_gzip(bencher: &mut Bencher, document: Document) {
+        bencher.iter(|| ::compress::gzip_compress(&::serde_and_bincode::encode(&document)))
     }
 
     #[bench]
-    fn rustc_and_cbor_decode_big(bencher: &mut Bencher) {
-        bench_decode(bencher, Option::RustcAndCbor, 1024 * 1024);
+    fn serde_and_bincode_encode_gzip_big_random(bencher: &mut Bencher) {
+        bench_encode_gzip(bencher, make_sample_data(1024 * 1024))
     }
 
     #[bench]
-    fn serde_and_bincode_decode_big(bencher: &mut Bencher) {
-        bench_decode(bencher, Option::SerdeAndBincode, 1024 * 1024);
+    fn serde_and_bincode_encode_gzip_big_zeros(bencher: &mut Bencher) {
+        bench_encode_gzip(bencher, make_compressible_sample_data(1024 * 1024))
     }
 
     #[bench]
-    fn rustc_and_bincode_decode_big(bencher: &mut Bencher) {
-        bench_decode(bencher, Option::RustcAndBincode, 1024 * 1024);
+    fn rustc_and_cbor_decode_big_from_reader(bencher: &mut Bencher) {
+        let bytes = ::rustc_and_cbor::encode(&make_sample_data(1024 * 1024));
+        bencher.iter(|| {
+            ::rustc_and_cbor::decode_from_reader::<_, Document>(Cursor::new(&bytes[..]))
+        })
     }
 }
@@ -0,0 +1,76 @@
+use serde::de::{Deserialize, Deserializer, Error, SeqVisitor, Visitor};
+
+const FIELDS: &'static [&'static str] = &["id", "name", "email"];
+
+#[derive(Debug, PartialEq)]
+pub struct ManualPerson {
+    id: u64,
+    name: String,
+    email: String,
+}
+
+impl Deserialize for ManualPerson {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<ManualPerson, D::Error> {
+        deserializer.visit_struct("Person", FIELDS, ManualPersonVisitor)
+    }
+}
+
+struct ManualPersonVisitor;
+
+impl Visitor for ManualPersonVisitor {
+    type Value = ManualPerson;
+
+    fn visit_seq<V: SeqVisitor>(&mut self, mut visitor: V) -> Result<ManualPerson, V::Error> {
+        let id = match try!(visitor.visit()) {
+            Some(id) => id,
+            None => return Err(Error::end_of_stream()),
+        };
+        let name = match try!(visitor.visit()) {
+            Some(name) => name,
+            None => return Err(Error::end_of_stream()),
+        };
+        let email = match try!(visitor.visit()) {
+            Some(email) => email,
+            None => return Err(Error::end_of_stream()),
+        };
+        try!(visitor.end());
+        Ok(ManualPerson {
+            id: id,
+            name: name,
+            email: email,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ManualPerson;
+    use {make_people, Person};
+    use test::Bencher;
+
+    #[test]
+    fn matches_derived() {
+        let people = make_people(1000);
+        let bytes = ::serde_and_bincode::encode(&people);
+        let derived = ::serde_and_bincode::decode::<Vec<Person>>(&bytes);
+        let manual = ::serde_and_bincode::decode::<Vec<ManualPerson>>(&bytes);
+        assert_eq!(derived.len(), manual.len());
+        for (derived, manual) in derived.iter().zip(manual.iter()) {
+            assert_eq!(derived.id, manual.id);
+            assert_eq!(derived.name, manual.name);
+            assert_eq!(derived.email, manual.email);
+        }
+    }
+
+    #[bench]
+    fn serde_and_bincode_decode_people_derived(bencher: &mut Bencher) {
+        let bytes = ::serde_and_bincode::encode(&make_people(1000));
+        bencher.iter(|| ::serde_and_bincode::decode::<Vec<Person>>(&bytes))
+    }
+
+    #[bench]
+    fn serde_and_bincode_decode_people_manual(bencher: &mut Bencher) {
+        let bytes = ::serde_and_bincode::encode(&make_people(1000));
+        bencher.iter(|| ::serde_and_bincode::decode::<Vec<ManualPerson>>(&bytes))
+    }
+}
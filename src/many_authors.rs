@@ -0,0 +1,64 @@
+use {make_people, Document};
+
+/// Returns a document with `count` authors and no content, so that decoding it is dominated by
+/// the two small `String` allocations made for each author.
+pub fn make_many_authors(count: usize) -> Document {
+    Document {
+        id: 1,
+        name: "Many authors".to_string(),
+        authors: make_people(count),
+        content: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::make_many_authors;
+    use bench::bench_decode;
+    use codec;
+    use test::Bencher;
+
+    const AUTHOR_COUNT: usize = 10_000;
+
+    #[cfg(feature = "counting_alloc")]
+    #[test]
+    fn decode_allocations() {
+        use counting_allocator::allocations;
+        use Document;
+        println!("");
+        println!("Allocations while decoding a document with {} authors:", AUTHOR_COUNT);
+        let document = make_many_authors(AUTHOR_COUNT);
+        for codec in codec::all::<Document>() {
+            let bytes = codec.encode(&document).unwrap();
+            let before = allocations();
+            codec.decode(&bytes).unwrap();
+            let count = allocations() - before;
+            // At the very least, the name and email of each author and the author list itself.
+            assert!(count > 2 * AUTHOR_COUNT, "{} made {} allocations", codec.name(), count);
+            println!("    {}: {} ({:.2} per author)",
+                     codec.name(),
+                     count,
+                     count as f64 / AUTHOR_COUNT as f64);
+        }
+    }
+
+    #[bench]
+    fn rustc_and_cbor_decode_many_authors(bencher: &mut Bencher) {
+        bench_decode(bencher, &codec::RustcAndCbor, make_many_authors(AUTHOR_COUNT));
+    }
+
+    #[bench]
+    fn serde_and_bincode_decode_many_authors(bencher: &mut Bencher) {
+        bench_decode(bencher, &codec::SerdeAndBincode, make_many_authors(AUTHOR_COUNT));
+    }
+
+    #[bench]
+    fn rustc_and_bincode_decode_many_authors(bencher: &mut Bencher) {
+        bench_decode(bencher, &codec::RustcAndBincode, make_many_authors(AUTHOR_COUNT));
+    }
+
+    #[bench]
+    fn serde_and_json_decode_many_authors(bencher: &mut Bencher) {
+        bench_decode(bencher, &codec::SerdeAndJson, make_many_authors(AUTHOR_COUNT));
+    }
+}
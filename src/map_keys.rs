@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct StringKeyed {
+    m: HashMap<String, u64>,
+}
+
+/// Formats whose maps only allow string keys can either stringify integer keys or refuse them.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct IntKeyed {
+    m: HashMap<u64, u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::hash::Hash;
+    use rustc_serialize::{Decodable, Encodable};
+    use serde::{Deserialize, Serialize};
+    use super::{IntKeyed, StringKeyed};
+
+    #[derive(Debug, PartialEq)]
+    enum Outcome {
+        Preserved,
+        Stringified,
+        Rejected,
+    }
+
+    fn make_int_keyed() -> IntKeyed {
+        let mut m = HashMap::new();
+        m.insert(1, 10);
+        m.insert(2, 20);
+        m.insert(u64::max_value(), 0);
+        IntKeyed { m: m }
+    }
+
+    fn make_string_keyed(int_keyed: &IntKeyed) -> StringKeyed {
+        let m = int_keyed.m.iter().map(|(key, value)| (key.to_string(), *value)).collect();
+        StringKeyed { m: m }
+    }
+
+    #[test]
+    fn string_keys() {
+        let string_keyed = make_string_keyed(&make_int_keyed());
+        for codec in ::codec::all::<StringKeyed>() {
+            let bytes = codec.encode(&string_keyed).unwrap();
+            assert_eq!(codec.decode(&bytes).unwrap(), string_keyed);
+        }
+    }
+
+    // JSON and the CBOR encoder both refuse integer keys outright rather than silently turning
+    // them into strings, so no codec here stringifies.  Should one start to, this will catch it.
+    #[test]
+    fn integer_keys() {
+        let expected = [("rustc_and_cbor", Outcome::Rejected),
+                        ("serde_and_bincode", Outcome::Preserved),
+                        ("rustc_and_bincode", Outcome::Preserved),
+                        ("serde_and_json", Outcome::Rejected),
+                        ("rustc_and_cbor_canonical", Outcome::Rejected)];
+        let int_keyed = make_int_keyed();
+        let string_keyed = make_string_keyed(&int_keyed);
+        let codecs = ::codec::all::<IntKeyed>().into_iter().zip(::codec::all::<StringKeyed>());
+        assert_eq!(::codec::all::<IntKeyed>().len(), expected.len());
+        for ((int_codec, string_codec), &(name, ref outcome)) in codecs.zip(expected.iter()) {
+            assert_eq!(int_codec.name(), name);
+            let actual = match int_codec.encode(&int_keyed) {
+                Err(_) => Outcome::Rejected,
+                Ok(bytes) => {
+                    match int_codec.decode(&bytes) {
+                        Ok(ref decoded) if *decoded == int_keyed => Outcome::Preserved,
+                        _ => {
+                            assert_eq!(string_codec.decode(&bytes).unwrap(), string_keyed);
+                            Outcome::Stringified
+                        }
+                    }
+                }
+            };
+            assert!(actual == *outcome, "{} handled integer keys as {:?}", name, actual);
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Duplicate {
+        FirstWins,
+        LastWins,
+        Rejected,
+    }
+
+    // `bytes` must hold a map with `key` given twice, first with the value 1 and then 2.
+    fn decode_duplicate<K>(name: &str, bytes: &[u8], key: K) -> Duplicate
+        where K: Eq + Hash,
+              HashMap<K, u64>: Encodable + Decodable + Serialize + Deserialize
+    {
+        let codec = ::codec::by_name::<HashMap<K, u64>>(name).unwrap();
+        match codec.decode(bytes) {
+            Err(_) => Duplicate::Rejected,
+            Ok(map) => {
+                assert_eq!(map.len(), 1);
+                match map[&key] {
+                    1 => Duplicate::FirstWins,
+                    2 => Duplicate::LastWins,
+                    value => panic!("{} decoded {} for the duplicate key", name, value),
+                }
+            }
+        }
+    }
+
+    // Neither CBOR nor JSON says what a duplicate key means.  Every decoder here inserts entries
+    // into the map one at a time, so the last value silently replaces the first.  The CBOR decoder
+    // refuses integer keys whether duplicated or not.  serde_json parses each string key into a
+    // u64 with `trim()` and `FromStr`, so distinct keys such as "1" and " 01" collide too.
+    #[test]
+    fn duplicate_keys() {
+        let cbor_string_keys = [0xa2, 0x61, b'a', 0x01, 0x61, b'a', 0x02];
+        let cbor_integer_keys = [0xa2, 0x01, 0x01, 0x01, 0x02];
+        for name in &["rustc_and_cbor", "rustc_and_cbor_canonical"] {
+            assert_eq!(decode_duplicate(name, &cbor_string_keys, "a".to_owned()),
+                       Duplicate::LastWins);
+            assert_eq!(decode_duplicate(name, &cbor_integer_keys, 1u64),
+                       Duplicate::Rejected);
+        }
+
+        let json = "serde_and_json";
+        assert_eq!(decode_duplicate(json, br#"{"a":1,"a":2}"#, "a".to_owned()),
+                   Duplicate::LastWins);
+        assert_eq!(decode_duplicate(json, br#"{"1":1,"1":2}"#, 1u64),
+                   Duplicate::LastWins);
+        assert_eq!(decode_duplicate(json, br#"{"1":1," 01":2}"#, 1u64),
+                   Duplicate::LastWins);
+    }
+}
@@ -0,0 +1,57 @@
+use byteorder::{BigEndian, ByteOrder};
+
+/// The prefix each binary codec should write before `len` zero bytes: bincode always writes a u64
+/// length, while CBOR (which sees a `Vec<u8>` from rustc-serialize as an array) needs the 8-byte
+/// form from 2^32 on.
+fn expected_prefix(codec: &str, len: u64) -> Vec<u8> {
+    let mut prefix = vec![0; 9];
+    match codec {
+        "rustc_and_cbor" if len > u32::max_value() as u64 => {
+            prefix[0] = 0x9b;
+            BigEndian::write_u64(&mut prefix[1..], len);
+        }
+        "rustc_and_cbor" => {
+            prefix[0] = 0x9a;
+            BigEndian::write_u32(&mut prefix[1..], len as u32);
+            prefix.truncate(5);
+        }
+        _ => {
+            BigEndian::write_u64(&mut prefix, len);
+            prefix.truncate(8);
+        }
+    }
+    prefix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expected_prefix;
+
+    // The content, its encoding (twice over for CBOR, which copies out of its encoder) and the
+    // decoded copy are all in memory at once, so this peaks at around 16 GiB.  Run with
+    // `cargo test --release max_collection_len -- --ignored`.
+    #[test]
+    #[ignore]
+    fn max_collection_len() {
+        let lengths = [1u64 << 31, u32::max_value() as u64, (1u64 << 32) + 1];
+        for &len in &lengths {
+            let mut content = vec![0u8; len as usize];
+            // Something to find at the far end, past where a truncated length would stop.
+            *content.last_mut().unwrap() = 1;
+            for codec in ::codec::all::<Vec<u8>>() {
+                if codec.name() != "rustc_and_cbor" && !codec.name().ends_with("bincode") {
+                    continue;
+                }
+                let bytes = codec.encode(&content).unwrap();
+                let prefix = expected_prefix(codec.name(), len);
+                assert!(bytes.starts_with(&prefix),
+                        "{} wrote the wrong prefix for {} bytes",
+                        codec.name(),
+                        len);
+                let decoded = codec.decode(&bytes).unwrap();
+                assert_eq!(decoded.len() as u64, len);
+                assert_eq!(decoded.last(), Some(&1));
+            }
+        }
+    }
+}
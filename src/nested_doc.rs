@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+/// A paged API response, nested four levels deep: the response holds users, who hold orders,
+/// which hold line items.  Unlike `Document` it carries no blob, so its cost is spread across many
+/// small strings, maps and optionals.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct NestedDoc {
+    pub request_id: String,
+    pub next_page: Option<u32>,
+    pub users: Vec<User>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct User {
+    pub id: u64,
+    pub name: String,
+    pub email: Option<String>,
+    pub tags: Vec<String>,
+    pub orders: Vec<Order>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct Order {
+    pub id: u64,
+    pub status: String,
+    pub items: Vec<Item>,
+    pub metadata: BTreeMap<String, String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct Item {
+    pub sku: String,
+    pub quantity: u32,
+    pub price_cents: u64,
+    pub note: Option<String>,
+}
+
+fn make_item(sku: usize, index: usize) -> Item {
+    Item {
+        sku: format!("SKU-{:05}", sku),
+        quantity: index as u32 + 1,
+        price_cents: 199 * (index as u64 + 1),
+        note: if index % 2 == 0 {
+            Some("gift wrap".to_owned())
+        } else {
+            None
+        },
+    }
+}
+
+fn make_order(user: usize, index: usize) -> Order {
+    let statuses = ["pending", "shipped", "delivered"];
+    let mut metadata = BTreeMap::new();
+    metadata.insert("channel".to_owned(), "web".to_owned());
+    metadata.insert("region".to_owned(), format!("eu-{}", index));
+    Order {
+        id: (user * 3 + index) as u64,
+        status: statuses[index % statuses.len()].to_owned(),
+        items: (0..(user + index) % 4 + 1).map(|k| make_item(user * 10 + k, k)).collect(),
+        metadata: metadata,
+    }
+}
+
+fn make_user(index: usize) -> User {
+    User {
+        id: index as u64,
+        name: format!("User {}", index),
+        email: if index % 2 == 0 {
+            Some(format!("user{}@example.com", index))
+        } else {
+            None
+        },
+        tags: vec!["customer".to_owned(), format!("cohort-{}", index % 7)],
+        orders: (0..3).map(|j| make_order(index, j)).collect(),
+    }
+}
+
+/// Returns a response with `user_count` users, each with three orders of up to four items.  Every
+/// other optional is left out.
+pub fn make_nested_doc(user_count: usize) -> NestedDoc {
+    NestedDoc {
+        request_id: "3f2c9a7e-51d4-4b8a-9c61-0d7e2f4a8b13".to_owned(),
+        next_page: Some(2),
+        users: (0..user_count).map(make_user).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{make_nested_doc, NestedDoc};
+    use codec;
+
+    const USER_COUNT: usize = 100;
+
+    #[test]
+    fn round_trips() {
+        let nested = make_nested_doc(USER_COUNT);
+        println!("");
+        println!("Size of a nested response with {} users:", USER_COUNT);
+        for codec in codec::all::<NestedDoc>() {
+            let bytes = codec.encode(&nested).unwrap();
+            assert!(codec.decode(&bytes).unwrap() == nested, "{} mangled it", codec.name());
+            println!("    {}: {} bytes", codec.name(), bytes.len());
+        }
+    }
+
+    bench_all! {
+        codec::RustcAndCbor => {
+            make_nested_doc(USER_COUNT) => rustc_and_cbor_encode_nested,
+                                           rustc_and_cbor_decode_nested;
+        }
+        codec::SerdeAndBincode => {
+            make_nested_doc(USER_COUNT) => serde_and_bincode_encode_nested,
+                                           serde_and_bincode_decode_nested;
+        }
+        codec::RustcAndBincode => {
+            make_nested_doc(USER_COUNT) => rustc_and_bincode_encode_nested,
+                                           rustc_and_bincode_decode_nested;
+        }
+        codec::SerdeAndJson => {
+            make_nested_doc(USER_COUNT) => serde_and_json_encode_nested,
+                                           serde_and_json_decode_nested;
+        }
+    }
+}
@@ -0,0 +1,57 @@
+/// A singly linked chain of values, for exercising nested `Option<Box<_>>`.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct Optionally {
+    inner: Option<Box<Optionally>>,
+    value: u64,
+}
+
+pub fn make_chain(depth: usize) -> Optionally {
+    let mut chain = Optionally {
+        inner: None,
+        value: 0,
+    };
+    for value in 1..depth as u64 {
+        chain = Optionally {
+            inner: Some(Box::new(chain)),
+            value: value,
+        };
+    }
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{make_chain, Optionally};
+
+    fn depth(chain: &Optionally) -> usize {
+        match chain.inner {
+            Some(ref inner) => 1 + depth(inner),
+            None => 1,
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let chain = make_chain(50);
+        assert_eq!(depth(&chain), 50);
+        for codec in ::codec::all::<Optionally>() {
+            let bytes = codec.encode(&chain).unwrap();
+            let decoded = codec.decode(&bytes).unwrap();
+            assert_eq!(depth(&decoded), 50);
+            assert_eq!(decoded, chain);
+        }
+    }
+
+    // Bincode writes one tag byte and a u64 per link; the self-describing codecs also repeat the
+    // field names.
+    #[test]
+    fn sizes() {
+        println!("");
+        println!("Size of a chain of 50 nested options:");
+        let chain = make_chain(50);
+        for codec in ::codec::all::<Optionally>() {
+            println!("    {}: {} bytes", codec.name(), codec.encode(&chain).unwrap().len());
+        }
+        assert_eq!(::serde_and_bincode::encode(&chain).len(), 50 * 9);
+    }
+}
@@ -0,0 +1,107 @@
+use borrow_map::{read_capacity, read_str, read_u64};
+use EvalError;
+
+/// The fewest bytes a bincode-encoded `Person` can take: its id and two string lengths.
+pub const MIN_PERSON_SIZE: u64 = 24;
+
+/// Person whose strings borrow from the buffer it was decoded from.
+#[derive(Debug, PartialEq)]
+pub struct PersonRef<'a> {
+    pub id: u64,
+    pub name: &'a str,
+    pub email: &'a str,
+}
+
+/// Reads a bincode-encoded `Person`, borrowing its strings from `bytes`.
+pub fn read_person<'a>(bytes: &mut &'a [u8]) -> Result<PersonRef<'a>, EvalError> {
+    let id = try!(read_u64(bytes));
+    let name = try!(read_str(bytes));
+    let email = try!(read_str(bytes));
+    Ok(PersonRef {
+        id: id,
+        name: name,
+        email: email,
+    })
+}
+
+/// Reads a bincode-encoded `Vec<Person>` without copying any strings, reading the layout by hand
+/// as `borrow_map::decode_borrowed` does, and advancing `bytes` past it.
+pub fn decode_people_borrowed<'a>(bytes: &mut &'a [u8]) -> Result<Vec<PersonRef<'a>>, EvalError> {
+    let (len, capacity) = try!(read_capacity(bytes, MIN_PERSON_SIZE));
+    let mut people = Vec::with_capacity(capacity);
+    for _ in 0..len {
+        people.push(try!(read_person(bytes)));
+    }
+    Ok(people)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode_people_borrowed;
+    use test::Bencher;
+    use {make_people, EvalError, Person};
+
+    const PEOPLE_COUNT: usize = 100_000;
+
+    #[test]
+    fn matches_owned() {
+        let people = make_people(1000);
+        let bytes = ::serde_and_bincode::encode(&people);
+        let borrowed = decode_people_borrowed(&mut &bytes[..]).unwrap();
+        assert_eq!(borrowed.len(), people.len());
+        let range = bytes.as_ptr() as usize..bytes.as_ptr() as usize + bytes.len();
+        for (borrowed, owned) in borrowed.iter().zip(&people) {
+            assert_eq!(borrowed.id, owned.id);
+            assert_eq!(borrowed.name, owned.name);
+            assert_eq!(borrowed.email, owned.email);
+            let address = borrowed.email.as_ptr() as usize;
+            assert!(address >= range.start && address < range.end);
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = ::serde_and_bincode::encode(&make_people(10));
+        assert_err!(decode_people_borrowed(&mut &bytes[..bytes.len() - 1]),
+                    EvalError::TruncatedInput);
+    }
+
+    #[cfg(feature = "counting_alloc")]
+    #[test]
+    fn decode_allocations() {
+        use counting_allocator::allocations;
+        println!("");
+        println!("Allocations while decoding {} people:", PEOPLE_COUNT);
+        let bytes = ::serde_and_bincode::encode(&make_people(PEOPLE_COUNT));
+
+        let before = allocations();
+        ::serde_and_bincode::decode::<Vec<Person>>(&bytes);
+        let owned_allocations = allocations() - before;
+
+        let before = allocations();
+        decode_people_borrowed(&mut &bytes[..]).unwrap();
+        let borrowed_allocations = allocations() - before;
+
+        assert_eq!(borrowed_allocations, 1);
+        println!("    String: {}", owned_allocations);
+        println!("    &str:   {}", borrowed_allocations);
+    }
+
+    #[bench]
+    fn serde_and_bincode_decode_people_owned(bencher: &mut Bencher) {
+        let bytes = ::serde_and_bincode::encode(&make_people(PEOPLE_COUNT));
+        bencher.iter(|| ::serde_and_bincode::decode::<Vec<Person>>(&bytes))
+    }
+
+    #[bench]
+    fn rustc_and_bincode_decode_people_owned(bencher: &mut Bencher) {
+        let bytes = ::rustc_and_bincode::encode(&make_people(PEOPLE_COUNT));
+        bencher.iter(|| ::rustc_and_bincode::decode::<Vec<Person>>(&bytes))
+    }
+
+    #[bench]
+    fn bincode_decode_people_borrowed(bencher: &mut Bencher) {
+        let bytes = ::serde_and_bincode::encode(&make_people(PEOPLE_COUNT));
+        bencher.iter(|| decode_people_borrowed(&mut &bytes[..]).unwrap().len())
+    }
+}
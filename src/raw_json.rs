@@ -0,0 +1,63 @@
+use std::io::Write;
+use serde::Serialize;
+use Document;
+
+/// A response wrapping a document which rarely changes between requests.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct CachedResponse {
+    pub request_id: u64,
+    pub document: Document,
+}
+
+/// A value already encoded as JSON, to be spliced into later output unchanged.
+pub struct RawJson(Vec<u8>);
+
+impl RawJson {
+    pub fn new<T: Serialize>(v: &T) -> RawJson {
+        RawJson(::serde_and_json::encode(v))
+    }
+}
+
+/// Encodes the same bytes as a `CachedResponse`, but copies in the pre-encoded document rather
+/// than serializing it again.  serde_json 0.6 has no `RawValue`, and a `Serialize` impl can't write
+/// bytes straight to the output, so the wrapper's JSON is written by hand.
+pub fn encode_response(request_id: u64, document: &RawJson) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(document.0.len() + 48);
+    write!(bytes, "{{\"request_id\":{},\"document\":", request_id).unwrap();
+    bytes.extend_from_slice(&document.0);
+    bytes.push(b'}');
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_response, CachedResponse, RawJson};
+    use test::Bencher;
+    use make_sample_data;
+
+    #[test]
+    fn matches_full_encode() {
+        let response = CachedResponse {
+            request_id: 42,
+            document: make_sample_data(1024),
+        };
+        let spliced = encode_response(42, &RawJson::new(&response.document));
+        assert_eq!(spliced, ::serde_and_json::encode(&response));
+        assert_eq!(::serde_and_json::decode::<CachedResponse>(&spliced), response);
+    }
+
+    #[bench]
+    fn serde_and_json_encode_big_response_full(bencher: &mut Bencher) {
+        let response = CachedResponse {
+            request_id: 42,
+            document: make_sample_data(1024 * 1024),
+        };
+        bencher.iter(|| ::serde_and_json::encode(&response))
+    }
+
+    #[bench]
+    fn serde_and_json_encode_big_response_spliced(bencher: &mut Bencher) {
+        let document = RawJson::new(&make_sample_data(1024 * 1024));
+        bencher.iter(|| encode_response(42, &document))
+    }
+}
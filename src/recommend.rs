@@ -0,0 +1,161 @@
+use std::f64;
+use std::time::Duration;
+use cold_cache::mean_time;
+use test::black_box;
+use {codec, Document};
+
+const ITERATIONS: u32 = 10;
+
+/// How much the caller cares about each of a codec's costs.  Only the ratios between the weights
+/// matter.
+#[derive(Clone, Copy, Debug)]
+pub struct Weights {
+    pub size: f64,
+    pub encode_speed: f64,
+    pub decode_speed: f64,
+}
+
+struct Costs {
+    name: &'static str,
+    size: f64,
+    encode: f64,
+    decode: f64,
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + duration.subsec_nanos() as f64 / 1e9
+}
+
+fn measure(doc: &Document) -> Vec<Costs> {
+    codec::all::<Document>()
+        .into_iter()
+        .map(|codec| {
+            let bytes = codec.encode(doc).unwrap();
+            let encode = mean_time(ITERATIONS, None, || {
+                black_box(codec.encode(doc).unwrap());
+            });
+            let decode = mean_time(ITERATIONS, None, || {
+                black_box(codec.decode(&bytes).unwrap());
+            });
+            Costs {
+                name: codec.name(),
+                size: bytes.len() as f64,
+                encode: seconds(encode),
+                decode: seconds(decode),
+            }
+        })
+        .collect()
+}
+
+fn lowest<F: Fn(&Costs) -> f64>(costs: &[Costs], cost: F) -> f64 {
+    costs.iter().map(cost).fold(f64::INFINITY, f64::min)
+}
+
+/// Returns the name of the codec with the lowest weighted cost.  Each cost is taken relative to the
+/// best codec's, so a codec scores `size + encode_speed + decode_speed` exactly when it's best at
+/// everything.
+fn select(costs: &[Costs], weights: Weights) -> &'static str {
+    let min_size = lowest(costs, |costs| costs.size);
+    let min_encode = lowest(costs, |costs| costs.encode);
+    let min_decode = lowest(costs, |costs| costs.decode);
+    let score = |costs: &Costs| {
+        weights.size * costs.size / min_size + weights.encode_speed * costs.encode / min_encode +
+        weights.decode_speed * costs.decode / min_decode
+    };
+    let mut best = &costs[0];
+    for candidate in &costs[1..] {
+        if score(candidate) < score(best) {
+            best = candidate;
+        }
+    }
+    best.name
+}
+
+/// Times each codec on `doc` and returns the name of the one with the lowest weighted cost.
+pub fn best_codec(doc: &Document, weights: Weights) -> &'static str {
+    select(&measure(doc), weights)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{best_codec, select, Costs, Weights};
+    use {codec, make_sample_data, Document};
+
+    // Sizes and times in the proportions a small document gives: CBOR's variable-length integers
+    // make it the most compact, but Bincode's fixed-width layout is far quicker to produce and
+    // consume.
+    fn costs() -> Vec<Costs> {
+        vec![Costs {
+                 name: "rustc_and_cbor",
+                 size: 40.0,
+                 encode: 2e-6,
+                 decode: 3e-6,
+             },
+             Costs {
+                 name: "serde_and_bincode",
+                 size: 56.0,
+                 encode: 2e-7,
+                 decode: 4e-7,
+             },
+             Costs {
+                 name: "serde_and_json",
+                 size: 80.0,
+                 encode: 1e-6,
+                 decode: 2e-6,
+             }]
+    }
+
+    #[test]
+    fn priorities() {
+        let by_size = Weights {
+            size: 1.0,
+            encode_speed: 0.0,
+            decode_speed: 0.0,
+        };
+        let by_speed = Weights {
+            size: 0.0,
+            encode_speed: 1.0,
+            decode_speed: 1.0,
+        };
+        assert_eq!(select(&costs(), by_size), "rustc_and_cbor");
+        assert_eq!(select(&costs(), by_speed), "serde_and_bincode");
+    }
+
+    #[test]
+    fn relative_costs() {
+        // Bincode scores 3.4, against 18.5 for CBOR and 12 for JSON.
+        let even = Weights {
+            size: 1.0,
+            encode_speed: 1.0,
+            decode_speed: 1.0,
+        };
+        assert_eq!(select(&costs(), even), "serde_and_bincode");
+        // Only the ratios between the weights matter.
+        let scaled = Weights {
+            size: 1000.0,
+            encode_speed: 1000.0,
+            decode_speed: 1000.0,
+        };
+        assert_eq!(select(&costs(), scaled), "serde_and_bincode");
+        // Bincode is 40% bigger than CBOR, which outweighs being 10 times quicker to encode and 7.5
+        // times quicker to decode once size counts 100 times as much.
+        let mostly_size = Weights {
+            size: 100.0,
+            encode_speed: 1.0,
+            decode_speed: 1.0,
+        };
+        assert_eq!(select(&costs(), mostly_size), "rustc_and_cbor");
+    }
+
+    // Which codec wins on real timings depends on the machine, so only check one is picked.
+    #[test]
+    fn picks_a_codec() {
+        let even = Weights {
+            size: 1.0,
+            encode_speed: 1.0,
+            decode_speed: 1.0,
+        };
+        let name = best_codec(&make_sample_data(0), even);
+        assert!(codec::all::<Document>().iter().any(|codec| codec.name() == name));
+    }
+}
@@ -0,0 +1,134 @@
+use rustc_serialize::{Decodable, Decoder, Encodable, Encoder};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use {make_sample_data, Document};
+
+/// An RPC-style response.  This wraps `Result` only because rustc-serialize has no impls for it;
+/// both libraries encode it as an enum with variants `Ok` and `Err`.
+#[derive(Debug, PartialEq)]
+pub struct Response(pub Result<Document, String>);
+
+impl Serialize for Response {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl Deserialize for Response {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<Response, D::Error> {
+        Deserialize::deserialize(deserializer).map(Response)
+    }
+}
+
+impl Encodable for Response {
+    fn encode<S: Encoder>(&self, encoder: &mut S) -> Result<(), S::Error> {
+        encoder.emit_enum("Result", |encoder| {
+            match self.0 {
+                Ok(ref document) => {
+                    encoder.emit_enum_variant("Ok", 0, 1, |encoder| {
+                        encoder.emit_enum_variant_arg(0, |encoder| document.encode(encoder))
+                    })
+                }
+                Err(ref error) => {
+                    encoder.emit_enum_variant("Err", 1, 1, |encoder| {
+                        encoder.emit_enum_variant_arg(0, |encoder| error.encode(encoder))
+                    })
+                }
+            }
+        })
+    }
+}
+
+impl Decodable for Response {
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<Response, D::Error> {
+        decoder.read_enum("Result", |decoder| {
+            decoder.read_enum_variant(&["Ok", "Err"], |decoder, index| {
+                match index {
+                    0 => {
+                        let document = try!(decoder.read_enum_variant_arg(0, Decodable::decode));
+                        Ok(Response(Ok(document)))
+                    }
+                    1 => {
+                        let error = try!(decoder.read_enum_variant_arg(0, Decodable::decode));
+                        Ok(Response(Err(error)))
+                    }
+                    _ => Err(decoder.error("unknown Result variant")),
+                }
+            })
+        })
+    }
+}
+
+/// Returns `count` responses, every third of which is an error with a multi-line message.
+pub fn make_results(count: usize) -> Vec<Result<Document, String>> {
+    (0..count)
+        .map(|i| {
+            if i % 3 == 2 {
+                Err(format!("request {} failed:\n\t\"timeout\" after 30s\n\\retrying\n", i))
+            } else {
+                Ok(make_sample_data(64))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{make_results, Response};
+    use {make_seeded_sample_data, Document};
+
+    fn make_responses(count: usize) -> Vec<Response> {
+        make_results(count).into_iter().map(Response).collect()
+    }
+
+    #[test]
+    fn round_trips() {
+        let responses = make_responses(30);
+        for codec in ::codec::all::<Vec<Response>>() {
+            let bytes = codec.encode(&responses).unwrap();
+            assert!(codec.decode(&bytes).unwrap() == responses,
+                    "{} mangled the responses",
+                    codec.name());
+        }
+        for codec in ::codec::all::<Response>() {
+            for response in &responses {
+                let bytes = codec.encode(response).unwrap();
+                assert_eq!(codec.decode(&bytes).unwrap(), *response);
+            }
+        }
+    }
+
+    #[test]
+    fn json_escapes_error() {
+        let response = make_responses(3).pop().unwrap();
+        let bytes = ::serde_and_json::encode(&response);
+        assert!(!bytes.contains(&b'\n') && !bytes.contains(&b'\t'));
+        assert_eq!(::serde_and_json::decode::<Response>(&bytes), response);
+    }
+
+    // The sample content is random, so the bare document has to be generated from the same seed.
+    #[test]
+    fn sizes() {
+        println!("");
+        println!("Size of a response, and its overhead over the bare document or message:");
+        let message = make_results(3).pop().unwrap().unwrap_err();
+        let ok = Response(Ok(make_seeded_sample_data(64)));
+        let error = Response(Err(message.clone()));
+        for codec in ::codec::all::<Response>() {
+            let document_bytes = ::codec::by_name::<Document>(codec.name())
+                                     .unwrap()
+                                     .encode(&make_seeded_sample_data(64))
+                                     .unwrap()
+                                     .len();
+            let message_bytes = ::codec::by_name::<String>(codec.name())
+                                    .unwrap()
+                                    .encode(&message)
+                                    .unwrap()
+                                    .len();
+            let ok_bytes = codec.encode(&ok).unwrap().len();
+            let error_bytes = codec.encode(&error).unwrap().len();
+            println!("Using {}", codec.name());
+            println!("    Ok:  {} bytes (+{})", ok_bytes, ok_bytes - document_bytes);
+            println!("    Err: {} bytes (+{})", error_bytes, error_bytes - message_bytes);
+        }
+    }
+}
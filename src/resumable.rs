@@ -0,0 +1,105 @@
+use byteorder::{BigEndian, ByteOrder};
+use serde::{Deserialize, Serialize};
+use chunking::{decode_chunked, encode_chunked};
+use crc32::{crc32, crc32_concat};
+use EvalError;
+
+// The chunk index and count written by `chunking`, then the checksum.
+const INDEX_SIZE: usize = 8;
+const HEADER_SIZE: usize = INDEX_SIZE + 4;
+
+/// Like `chunking::encode_chunked`, but with a CRC-32 following each chunk's index and count, so
+/// that a receiver can check chunks individually and ask again for any which arrived corrupted.
+/// The checksum covers the index and count as well as the payload, so a chunk can't pass with the
+/// wrong index.
+///
+/// Panics under the same conditions as `encode_chunked`.
+pub fn encode<T: Serialize>(v: &T, chunk_size: usize) -> Vec<Vec<u8>> {
+    encode_chunked(v, chunk_size)
+        .into_iter()
+        .map(|chunk| {
+            let mut crc = [0; 4];
+            BigEndian::write_u32(&mut crc, crc32(&chunk));
+            let mut with_crc = Vec::with_capacity(chunk.len() + crc.len());
+            with_crc.extend_from_slice(&chunk[..INDEX_SIZE]);
+            with_crc.extend_from_slice(&crc);
+            with_crc.extend_from_slice(&chunk[INDEX_SIZE..]);
+            with_crc
+        })
+        .collect()
+}
+
+/// Checks a single chunk produced by `encode`, returning its index if the checksum matches.
+pub fn verify(chunk: &[u8]) -> Result<u32, EvalError> {
+    if chunk.len() < HEADER_SIZE {
+        return Err(EvalError::TruncatedChunk);
+    }
+    let index = BigEndian::read_u32(&chunk[..4]);
+    let expected = crc32_concat(&[&chunk[..INDEX_SIZE], &chunk[HEADER_SIZE..]]);
+    if BigEndian::read_u32(&chunk[INDEX_SIZE..HEADER_SIZE]) != expected {
+        return Err(EvalError::ChunkChecksumMismatch(index));
+    }
+    Ok(index)
+}
+
+/// Verifies every chunk produced by `encode`, then reassembles them.  The chunks must be passed
+/// in order.
+pub fn decode<T: Deserialize>(chunks: &[Vec<u8>]) -> Result<T, EvalError> {
+    let mut stripped = Vec::with_capacity(chunks.len());
+    for chunk in chunks {
+        try!(verify(chunk));
+        let mut without_crc = chunk[..INDEX_SIZE].to_vec();
+        without_crc.extend_from_slice(&chunk[HEADER_SIZE..]);
+        stripped.push(without_crc);
+    }
+    decode_chunked(&stripped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, verify};
+    use {make_sample_data, Document, EvalError};
+
+    #[test]
+    fn round_trip() {
+        let document = make_sample_data(1024 * 1024);
+        let chunks = encode(&document, 64 * 1024);
+        assert_eq!(chunks.len(), 17);
+        for (index, chunk) in chunks.iter().enumerate() {
+            assert_eq!(verify(chunk).unwrap(), index as u32);
+        }
+        assert_eq!(decode::<Document>(&chunks).unwrap(), document);
+    }
+
+    #[test]
+    fn corrupt_chunk() {
+        let mut chunks = encode(&make_sample_data(1024 * 1024), 64 * 1024);
+        chunks[5][1000] ^= 1;
+        let corrupt = chunks.iter().filter(|chunk| verify(chunk).is_err()).count();
+        assert_eq!(corrupt, 1);
+        assert_err!(decode::<Document>(&chunks), EvalError::ChunkChecksumMismatch(5));
+    }
+
+    #[test]
+    fn corrupt_header() {
+        let mut chunks = encode(&make_sample_data(1024 * 1024), 64 * 1024);
+        // The low byte of the count, then of the index.
+        chunks[5][7] ^= 1;
+        assert_err!(verify(&chunks[5]), EvalError::ChunkChecksumMismatch(5));
+        chunks[6][3] ^= 1;
+        assert_err!(verify(&chunks[6]), EvalError::ChunkChecksumMismatch(7));
+    }
+
+    #[test]
+    fn truncated_chunk() {
+        let mut chunks = encode(&make_sample_data(1024), 64 * 1024);
+        chunks[0].truncate(11);
+        assert_err!(decode::<Document>(&chunks), EvalError::TruncatedChunk);
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk size must be greater than zero")]
+    fn zero_chunk_size() {
+        encode(&make_sample_data(1024), 0);
+    }
+}
@@ -0,0 +1,84 @@
+use std::cell::RefCell;
+use std::mem;
+use serde::de::{Deserialize, Deserializer, SeqVisitor, Visitor};
+use {Document, EvalError, Person};
+
+// serde 0.6 has no way to pass state into a nested `Deserialize`, so the buffer to reuse is handed
+// over through a thread-local instead.
+thread_local!(static CONTENT_BUFFER: RefCell<Vec<u8>> = RefCell::new(Vec::new()));
+
+struct ReusedContent(Vec<u8>);
+
+impl Deserialize for ReusedContent {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<ReusedContent, D::Error> {
+        deserializer.visit_seq(ReusedContentVisitor)
+    }
+}
+
+struct ReusedContentVisitor;
+
+impl Visitor for ReusedContentVisitor {
+    type Value = ReusedContent;
+
+    fn visit_seq<V: SeqVisitor>(&mut self, mut visitor: V) -> Result<ReusedContent, V::Error> {
+        let mut content = CONTENT_BUFFER.with(|buffer| {
+            mem::replace(&mut *buffer.borrow_mut(), vec![])
+        });
+        content.clear();
+        while let Some(byte) = try!(visitor.visit()) {
+            content.push(byte);
+        }
+        try!(visitor.end());
+        Ok(ReusedContent(content))
+    }
+}
+
+#[derive(Deserialize)]
+struct DocumentReusedContent {
+    id: u64,
+    name: String,
+    authors: Vec<Person>,
+    content: ReusedContent,
+}
+
+/// Decodes a bincode-encoded `Document`, reading its content into `buffer` rather than a new
+/// allocation.  Pass the previous document's content back in to avoid allocating in a loop.
+pub fn decode_reusing(bytes: &[u8], buffer: Vec<u8>) -> Result<Document, EvalError> {
+    CONTENT_BUFFER.with(|cell| *cell.borrow_mut() = buffer);
+    let document: DocumentReusedContent = try!(::serde_and_bincode::decode_result(bytes));
+    Ok(Document {
+        id: document.id,
+        name: document.name,
+        authors: document.authors,
+        content: document.content.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+    use super::decode_reusing;
+    use make_sample_data;
+    use test::Bencher;
+
+    #[test]
+    fn reuses_buffer() {
+        let document = make_sample_data(1024 * 1024);
+        let bytes = ::serde_and_bincode::encode(&document);
+        let buffer = Vec::with_capacity(2 * 1024 * 1024);
+        let address = buffer.as_ptr();
+        let decoded = decode_reusing(&bytes, buffer).unwrap();
+        assert_eq!(decoded.content.as_ptr(), address);
+        assert_eq!(decoded, document);
+    }
+
+    #[bench]
+    fn serde_and_bincode_decode_big_reused_content(bencher: &mut Bencher) {
+        let bytes = ::serde_and_bincode::encode(&make_sample_data(1024 * 1024));
+        let mut buffer = Vec::new();
+        bencher.iter(|| {
+            let document = decode_reusing(&bytes, mem::replace(&mut buffer, vec![])).unwrap();
+            buffer = document.content;
+        })
+    }
+}
@@ -0,0 +1,66 @@
+use std::rc::Rc;
+use {make_people, Document, Person};
+
+/// Document whose authors may share a single `Person`.  Encoders don't track sharing, so each
+/// reference is encoded in full.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct DocumentShared {
+    id: u64,
+    name: String,
+    authors: Vec<Rc<Person>>,
+    content: Vec<u8>,
+}
+
+/// Returns a document with the same author `n` times over.
+pub fn make_shared_authors(n: usize) -> DocumentShared {
+    let author = Rc::new(make_people(1).pop().unwrap());
+    DocumentShared {
+        id: 1,
+        name: "Shared".to_string(),
+        authors: (0..n).map(|_| author.clone()).collect(),
+        content: vec![],
+    }
+}
+
+/// Returns a document with `n` distinct copies of the author used by `make_shared_authors`.
+fn make_distinct_authors(n: usize) -> Document {
+    Document {
+        id: 1,
+        name: "Shared".to_string(),
+        authors: (0..n).map(|_| make_people(1).pop().unwrap()).collect(),
+        content: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{make_distinct_authors, make_shared_authors, DocumentShared};
+    use {Document, Person};
+
+    #[test]
+    fn sizes() {
+        println!("");
+        println!("Size of 1000 references to one author:");
+        let shared = make_shared_authors(1000);
+        let distinct = make_distinct_authors(1000);
+        let single = make_shared_authors(1);
+        let codecs = ::codec::all::<DocumentShared>().into_iter().zip(::codec::all::<Document>());
+        for (shared_codec, distinct_codec) in codecs {
+            let shared_bytes = shared_codec.encode(&shared).unwrap();
+            assert_eq!(shared_bytes, distinct_codec.encode(&distinct).unwrap());
+            let single_len = shared_codec.encode(&single).unwrap().len();
+            println!("Using {}", shared_codec.name());
+            println!("    1 author:     {} bytes", single_len);
+            println!("    1000 shared:  {} bytes ({:.0}x)",
+                     shared_bytes.len(),
+                     shared_bytes.len() as f64 / single_len as f64);
+
+            // Decoding gives every author its own allocation.
+            let decoded = shared_codec.decode(&shared_bytes).unwrap();
+            assert_eq!(decoded, shared);
+            let first: *const Person = &*decoded.authors[0];
+            let second: *const Person = &*decoded.authors[1];
+            assert!(first != second);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use {Document, Person};
+
+/// `Person` with single-letter field names on the wire.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ShortPerson {
+    #[serde(rename="i")]
+    id: u64,
+    #[serde(rename="n")]
+    name: String,
+    #[serde(rename="e")]
+    email: String,
+}
+
+/// `Document` with single-letter field names on the wire.  rustc-serialize has no equivalent of
+/// `rename`, so this only applies to the serde codecs.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ShortDocument {
+    #[serde(rename="i")]
+    id: u64,
+    #[serde(rename="n")]
+    name: String,
+    #[serde(rename="a")]
+    authors: Vec<ShortPerson>,
+    #[serde(rename="c")]
+    content: Vec<u8>,
+}
+
+impl From<Person> for ShortPerson {
+    fn from(person: Person) -> ShortPerson {
+        ShortPerson {
+            id: person.id,
+            name: person.name,
+            email: person.email,
+        }
+    }
+}
+
+impl From<Document> for ShortDocument {
+    fn from(document: Document) -> ShortDocument {
+        ShortDocument {
+            id: document.id,
+            name: document.name,
+            authors: document.authors.into_iter().map(ShortPerson::from).collect(),
+            content: document.content,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShortDocument;
+    use make_sample_data;
+
+    #[test]
+    fn sizes() {
+        println!("");
+        println!("Size of 1000 documents with full and shortened field names:");
+        let full: Vec<_> = (0..1000).map(|_| make_sample_data(16)).collect();
+        let short: Vec<_> = (0..1000).map(|_| ShortDocument::from(make_sample_data(16))).collect();
+
+        let json_full = ::serde_and_json::encode(&full);
+        let json_short = ::serde_and_json::encode(&short);
+        assert!(json_short.len() < json_full.len());
+        assert_eq!(::serde_and_json::decode::<Vec<ShortDocument>>(&json_short), short);
+        println!("Using JSON format with serde");
+        println!("    full names:  {} bytes", json_full.len());
+        println!("    short names: {} bytes", json_short.len());
+
+        // Bincode doesn't write field names at all.
+        let bincode_full = ::serde_and_bincode::encode(&full).len();
+        let bincode_short = ::serde_and_bincode::encode(&short).len();
+        assert_eq!(bincode_short, bincode_full);
+        println!("Using Bincode format with serde");
+        println!("    full names:  {} bytes", bincode_full);
+        println!("    short names: {} bytes", bincode_short);
+    }
+}
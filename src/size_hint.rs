@@ -0,0 +1,51 @@
+use bincode::serde;
+use serde::Serialize;
+
+/// Returns the length of the bincode encoding of `v` without producing it.
+pub fn serialized_len<T: Serialize>(v: &T) -> usize {
+    serde::serialized_size(v) as usize
+}
+
+/// Encodes `v` with bincode into a `Vec` allocated up front at exactly the right size, at the cost
+/// of an extra pass over `v` to measure it.
+pub fn encode_presized<T: Serialize>(v: &T) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(serialized_len(v));
+    ::serde_and_bincode::encode_into(&mut bytes, v);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_presized, serialized_len};
+    use {make_people, make_sample_data};
+    use test::Bencher;
+
+    #[test]
+    fn matches_encode() {
+        let document = make_sample_data(1024 * 1024);
+        assert_eq!(serialized_len(&document), ::serde_and_bincode::encode(&document).len());
+        let people = make_people(100);
+        assert_eq!(serialized_len(&people), ::serde_and_bincode::encode(&people).len());
+    }
+
+    #[test]
+    fn presized() {
+        let document = make_sample_data(1024 * 1024);
+        let bytes = encode_presized(&document);
+        assert_eq!(bytes.capacity(), bytes.len());
+        assert_eq!(bytes, ::serde_and_bincode::encode(&document));
+    }
+
+    #[bench]
+    fn serde_and_bincode_serialized_len_big(bencher: &mut Bencher) {
+        let document = make_sample_data(1024 * 1024);
+        bencher.iter(|| serialized_len(&document))
+    }
+
+    // Compare with `serde_and_bincode_encode_big`, which lets the `Vec` grow from empty.
+    #[bench]
+    fn serde_and_bincode_encode_big_presized(bencher: &mut Bencher) {
+        let document = make_sample_data(1024 * 1024);
+        bencher.iter(|| encode_presized(&document))
+    }
+}
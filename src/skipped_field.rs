@@ -0,0 +1,59 @@
+use Person;
+
+/// Document carrying a field which must never go over the wire.  serde 0.6 has no `skip`
+/// attribute, so it's spelt `skip_serializing` plus `default`.  rustc-serialize has no equivalent
+/// at all, so the rustc codecs encode the token like any other field.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct DocumentWithSecret {
+    id: u64,
+    name: String,
+    authors: Vec<Person>,
+    content: Vec<u8>,
+    #[serde(skip_serializing, default)]
+    internal_token: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DocumentWithSecret;
+    use make_sample_data;
+
+    const TOKEN: &'static str = "0123456789abcdef-internal-token";
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|window| window == needle)
+    }
+
+    // The rustc codecs leak the token.  Of the serde codecs, JSON falls back to the default since
+    // the field is simply missing, but Bincode has no field names and so runs out of input looking
+    // for the token.
+    #[test]
+    fn skipped_token() {
+        let expected = [("rustc_and_cbor", true, Some(TOKEN)),
+                        ("serde_and_bincode", false, None),
+                        ("rustc_and_bincode", true, Some(TOKEN)),
+                        ("serde_and_json", false, Some("")),
+                        ("rustc_and_cbor_canonical", true, Some(TOKEN))];
+        let document = make_sample_data(1024);
+        let secret = DocumentWithSecret {
+            id: document.id,
+            name: document.name,
+            authors: document.authors,
+            content: document.content,
+            internal_token: TOKEN.to_string(),
+        };
+        let codecs = ::codec::all::<DocumentWithSecret>();
+        assert_eq!(codecs.len(), expected.len());
+        for (codec, &(name, leaks, decoded_token)) in codecs.into_iter().zip(expected.iter()) {
+            assert_eq!(codec.name(), name);
+            let bytes = codec.encode(&secret).unwrap();
+            assert_eq!(contains(&bytes, TOKEN.as_bytes()), leaks);
+            match (codec.decode(&bytes), decoded_token) {
+                (Ok(decoded), Some(token)) => assert_eq!(decoded.internal_token, token),
+                (Err(_), None) => (),
+                (Ok(_), None) => panic!("{} should fail to decode", name),
+                (Err(error), Some(_)) => panic!("{} failed to decode: {}", name, error),
+            }
+        }
+    }
+}
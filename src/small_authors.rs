@@ -0,0 +1,71 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::{SeqVisitor, Visitor};
+use smallvec::SmallVec;
+use Person;
+
+/// Author list which keeps up to four people inline.  It is encoded exactly like `Vec<Person>`.
+pub struct SmallAuthors(pub SmallVec<[Person; 4]>);
+
+impl Serialize for SmallAuthors {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        self.0[..].serialize(serializer)
+    }
+}
+
+impl Deserialize for SmallAuthors {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<SmallAuthors, D::Error> {
+        deserializer.visit_seq(SmallAuthorsVisitor)
+    }
+}
+
+struct SmallAuthorsVisitor;
+
+impl Visitor for SmallAuthorsVisitor {
+    type Value = SmallAuthors;
+
+    fn visit_seq<V: SeqVisitor>(&mut self, mut visitor: V) -> Result<SmallAuthors, V::Error> {
+        let mut authors = SmallVec::new();
+        while let Some(person) = try!(visitor.visit()) {
+            authors.push(person);
+        }
+        try!(visitor.end());
+        Ok(SmallAuthors(authors))
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DocumentSmallVec {
+    id: u64,
+    name: String,
+    authors: SmallAuthors,
+    content: Vec<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DocumentSmallVec;
+    use {make_sample_data, Document};
+    use test::Bencher;
+
+    #[test]
+    fn same_encoding_as_vec() {
+        let document = make_sample_data(16);
+        let bytes = ::serde_and_bincode::encode(&document);
+        let decoded = ::serde_and_bincode::decode::<DocumentSmallVec>(&bytes);
+        assert!(!decoded.authors.0.spilled());
+        assert_eq!(&decoded.authors.0[..], &document.authors[..]);
+        assert_eq!(::serde_and_bincode::encode(&decoded), bytes);
+    }
+
+    #[bench]
+    fn serde_and_bincode_decode_small_authors_vec(bencher: &mut Bencher) {
+        let bytes = ::serde_and_bincode::encode(&make_sample_data(0));
+        bencher.iter(|| ::serde_and_bincode::decode::<Document>(&bytes))
+    }
+
+    #[bench]
+    fn serde_and_bincode_decode_small_authors_smallvec(bencher: &mut Bencher) {
+        let bytes = ::serde_and_bincode::encode(&make_sample_data(0));
+        bencher.iter(|| ::serde_and_bincode::decode::<DocumentSmallVec>(&bytes))
+    }
+}
@@ -0,0 +1,42 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+fn path(name: &str) -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("tests");
+    path.push("snapshots");
+    path.push(format!("{}.bin", name));
+    path
+}
+
+/// Compares `bytes` against the golden file `tests/snapshots/<name>.bin`, panicking with the first
+/// differing offset if they don't match.  If the file doesn't exist yet, or the `UPDATE_SNAPSHOTS`
+/// environment variable is set, the file is (re)written from `bytes` instead.
+pub fn check(name: &str, bytes: &[u8]) {
+    let path = path(name);
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() || !path.exists() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        File::create(&path).unwrap().write_all(bytes).unwrap();
+        return;
+    }
+
+    let mut expected = Vec::new();
+    File::open(&path).unwrap().read_to_end(&mut expected).unwrap();
+    if let Some(offset) = expected.iter().zip(bytes).position(|(lhs, rhs)| lhs != rhs) {
+        panic!("{}: encoding differs from {} at byte {} (expected {:#04x}, got {:#04x})",
+               name,
+               path.display(),
+               offset,
+               expected[offset],
+               bytes[offset]);
+    }
+    if expected.len() != bytes.len() {
+        panic!("{}: encoding is {} bytes, but {} is {} bytes",
+               name,
+               bytes.len(),
+               path.display(),
+               expected.len());
+    }
+}
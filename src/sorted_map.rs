@@ -0,0 +1,105 @@
+use std::collections::{BTreeMap, HashMap};
+
+/// A map which always encodes its entries in key order.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct Sorted {
+    pub m: BTreeMap<String, u64>,
+}
+
+/// The same map, encoded in whatever order its hasher leaves the entries.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct Hashed {
+    pub m: HashMap<String, u64>,
+}
+
+/// Builds `count` entries.  The keys are all the same length, so key order is also the order
+/// canonical CBOR uses.
+fn make_entries(count: u64) -> Vec<(String, u64)> {
+    (0..count).map(|i| (format!("key{:08}", i), i * 7919)).collect()
+}
+
+pub fn make_sorted(count: u64) -> Sorted {
+    Sorted { m: make_entries(count).into_iter().collect() }
+}
+
+pub fn make_hashed(count: u64) -> Hashed {
+    Hashed { m: make_entries(count).into_iter().collect() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{make_entries, make_hashed, make_sorted, Hashed, Sorted};
+    use bench::bench_encode;
+    use codec;
+    use test::Bencher;
+
+    const ENTRY_COUNT: u64 = 10_000;
+
+    #[test]
+    fn sizes() {
+        let sorted = make_sorted(ENTRY_COUNT);
+        let hashed = make_hashed(ENTRY_COUNT);
+        println!("");
+        println!("Size of a map with {} entries:", ENTRY_COUNT);
+        let codecs = codec::all::<Sorted>().into_iter().zip(codec::all::<Hashed>());
+        for (sorted_codec, hashed_codec) in codecs {
+            let sorted_bytes = sorted_codec.encode(&sorted).unwrap();
+            let hashed_bytes = hashed_codec.encode(&hashed).unwrap();
+            println!("    {}: BTreeMap {} bytes, HashMap {} bytes",
+                     sorted_codec.name(),
+                     sorted_bytes.len(),
+                     hashed_bytes.len());
+            // Only the order differs, so neither is any bigger.
+            assert_eq!(sorted_bytes.len(), hashed_bytes.len());
+            assert_eq!(sorted_codec.decode(&sorted_bytes).unwrap(), sorted);
+            assert_eq!(hashed_codec.decode(&hashed_bytes).unwrap(), hashed);
+        }
+    }
+
+    #[test]
+    fn deterministic() {
+        let sorted = make_sorted(ENTRY_COUNT);
+        let reversed = Sorted { m: make_entries(ENTRY_COUNT).into_iter().rev().collect() };
+        for codec in codec::all::<Sorted>() {
+            assert_eq!(codec.encode(&sorted).unwrap(), codec.encode(&reversed).unwrap());
+        }
+        // Plain CBOR of a BTreeMap is already canonical, at least while the keys are all the same
+        // length, so it matches what the canonical codec has to sort a HashMap to get.
+        let canonical = ::cbor_canonical::encode_result(&make_hashed(ENTRY_COUNT)).unwrap();
+        assert_eq!(::rustc_and_cbor::encode(&sorted), canonical);
+    }
+
+    fn sorted() -> Sorted {
+        make_sorted(ENTRY_COUNT)
+    }
+
+    fn hashed() -> Hashed {
+        make_hashed(ENTRY_COUNT)
+    }
+
+    bench_all! {
+        codec::RustcAndCbor => {
+            sorted() => rustc_and_cbor_encode_btree_map, rustc_and_cbor_decode_btree_map;
+            hashed() => rustc_and_cbor_encode_hash_map, rustc_and_cbor_decode_hash_map;
+        }
+        codec::SerdeAndBincode => {
+            sorted() => serde_and_bincode_encode_btree_map, serde_and_bincode_decode_btree_map;
+            hashed() => serde_and_bincode_encode_hash_map, serde_and_bincode_decode_hash_map;
+        }
+        codec::RustcAndBincode => {
+            sorted() => rustc_and_bincode_encode_btree_map, rustc_and_bincode_decode_btree_map;
+            hashed() => rustc_and_bincode_encode_hash_map, rustc_and_bincode_decode_hash_map;
+        }
+        codec::SerdeAndJson => {
+            sorted() => serde_and_json_encode_btree_map, serde_and_json_decode_btree_map;
+            hashed() => serde_and_json_encode_hash_map, serde_and_json_decode_hash_map;
+        }
+    }
+
+    // The canonical codec sorts a HashMap itself, which is the alternative to a BTreeMap when
+    // stable output is only needed on the wire.
+    #[bench]
+    fn rustc_and_cbor_canonical_encode_hash_map(bencher: &mut Bencher) {
+        bench_encode(bencher, &codec::RustcAndCborCanonical, hashed());
+    }
+}
@@ -0,0 +1,83 @@
+use std::io::{ErrorKind, Read};
+use {Document, EvalError, Person};
+
+/// Encodes everything but the content with bincode, followed by the content's length, so that a
+/// small manifest can be sent ahead of the content.
+pub fn encode_header(document: &Document) -> Vec<u8> {
+    ::serde_and_bincode::encode(&(document.id,
+                                  &document.name,
+                                  &document.authors,
+                                  document.content.len() as u64))
+}
+
+/// Returns the bytes to send after the header: the raw content, unencoded.
+pub fn encode_content(document: &Document) -> &[u8] {
+    &document.content
+}
+
+/// Decodes a header written by `encode_header`, then reads exactly as much content as it declares
+/// from `content`.
+pub fn decode<R: Read>(header: &[u8], content: R) -> Result<Document, EvalError> {
+    let (id, name, authors, len): (u64, String, Vec<Person>, u64) =
+        try!(::serde_and_bincode::decode_result(header));
+    // Don't trust the declared length for the allocation; read until it's reached or the reader
+    // runs dry.
+    let mut bytes = Vec::new();
+    match content.take(len).read_to_end(&mut bytes) {
+        Ok(_) if bytes.len() as u64 == len => (),
+        Ok(_) => return Err(EvalError::TruncatedInput),
+        Err(ref error) if error.kind() == ErrorKind::UnexpectedEof => {
+            return Err(EvalError::TruncatedInput)
+        }
+        Err(error) => return Err(EvalError::ContentRead(error)),
+    }
+    Ok(Document {
+        id: id,
+        name: name,
+        authors: authors,
+        content: bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+    use super::{decode, encode_content, encode_header};
+    use {make_sample_data, EvalError};
+
+    #[test]
+    fn round_trips() {
+        let document = make_sample_data(1024 * 1024);
+        let header = encode_header(&document);
+        assert!(header.len() < 1024);
+        let content = encode_content(&document);
+        assert_eq!(decode(&header, content).unwrap(), document);
+        // A stream of the header followed by the content is just as good, given the header length.
+        let mut stream = header.clone();
+        stream.extend_from_slice(content);
+        assert_eq!(decode(&stream[..header.len()], &stream[header.len()..]).unwrap(), document);
+    }
+
+    #[test]
+    fn short_content() {
+        let document = make_sample_data(1024);
+        let content = &encode_content(&document)[..1023];
+        assert_err!(decode(&encode_header(&document), content), EvalError::TruncatedInput);
+    }
+
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::ConnectionReset, "connection reset"))
+        }
+    }
+
+    #[test]
+    fn content_read_error() {
+        assert_err!(decode(&encode_header(&make_sample_data(1024)), Broken),
+                    EvalError::ContentRead(ref error) => {
+                        assert_eq!(error.kind(), io::ErrorKind::ConnectionReset)
+                    });
+    }
+}
@@ -0,0 +1,208 @@
+use rustc_serialize::{Decodable, Decoder, Encodable, Encoder};
+use serde::de::{self, Deserialize, Deserializer, MapVisitor, Visitor};
+use serde::ser::impls::MapIteratorVisitor;
+use serde::{Serialize, Serializer};
+
+/// An enum tagged internally, as newer serde derives with `#[serde(tag = "type")]`: each variant
+/// is a map of its fields plus a `"type"` entry naming it, so `Circle { r: 1.5 }` is
+/// `{"type":"c","r":1.5}`.
+///
+/// serde 0.6 has no `tag` attribute, so the impls are written by hand.  The serde one does what
+/// the derive does: since the tag needn't come first, it buffers each value with
+/// `Deserializer::visit`, which only self-describing formats support.  rustc-serialize has no
+/// equivalent, so its impl reads every field other than the tag as an `f64` instead.
+#[derive(Debug, PartialEq)]
+pub enum Shape {
+    Circle {
+        r: f64,
+    },
+    Square {
+        s: f64,
+    },
+}
+
+/// A buffered map value of whatever type the input held.
+enum Content {
+    Str(String),
+    F64(f64),
+}
+
+impl Shape {
+    fn fields(&self) -> Vec<(&'static str, Content)> {
+        match *self {
+            Shape::Circle { r } => {
+                vec![("type", Content::Str("c".to_owned())), ("r", Content::F64(r))]
+            }
+            Shape::Square { s } => {
+                vec![("type", Content::Str("Square".to_owned())), ("s", Content::F64(s))]
+            }
+        }
+    }
+
+    fn from_fields(fields: Vec<(String, Content)>) -> Result<Shape, String> {
+        let (mut tag, mut r, mut s) = (None, None, None);
+        for (key, value) in fields {
+            match (&key[..], value) {
+                ("type", Content::Str(value)) => tag = Some(value),
+                ("r", Content::F64(value)) => r = Some(value),
+                ("s", Content::F64(value)) => s = Some(value),
+                (key, _) => return Err(format!("unexpected field {}", key)),
+            }
+        }
+        match (tag.as_ref().map(|tag| &tag[..]), r, s) {
+            (Some("c"), Some(r), None) => Ok(Shape::Circle { r: r }),
+            (Some("Square"), None, Some(s)) => Ok(Shape::Square { s: s }),
+            (Some(tag), _, _) => Err(format!("wrong fields for shape {}", tag)),
+            (None, _, _) => Err("missing type".to_owned()),
+        }
+    }
+}
+
+impl Serialize for Content {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        match *self {
+            Content::Str(ref value) => serializer.visit_str(value),
+            Content::F64(value) => serializer.visit_f64(value),
+        }
+    }
+}
+
+impl Deserialize for Content {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<Content, D::Error> {
+        deserializer.visit(ContentVisitor)
+    }
+}
+
+struct ContentVisitor;
+
+impl Visitor for ContentVisitor {
+    type Value = Content;
+
+    fn visit_str<E: de::Error>(&mut self, value: &str) -> Result<Content, E> {
+        Ok(Content::Str(value.to_owned()))
+    }
+
+    fn visit_f64<E: de::Error>(&mut self, value: f64) -> Result<Content, E> {
+        Ok(Content::F64(value))
+    }
+
+    // JSON doesn't distinguish floats from integers, so a whole number may come back as either.
+    fn visit_u64<E: de::Error>(&mut self, value: u64) -> Result<Content, E> {
+        Ok(Content::F64(value as f64))
+    }
+
+    fn visit_i64<E: de::Error>(&mut self, value: i64) -> Result<Content, E> {
+        Ok(Content::F64(value as f64))
+    }
+}
+
+impl Serialize for Shape {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        let fields = self.fields();
+        let len = fields.len();
+        serializer.visit_map(MapIteratorVisitor::new(fields.into_iter(), Some(len)))
+    }
+}
+
+impl Deserialize for Shape {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<Shape, D::Error> {
+        deserializer.visit_map(ShapeVisitor)
+    }
+}
+
+struct ShapeVisitor;
+
+impl Visitor for ShapeVisitor {
+    type Value = Shape;
+
+    fn visit_map<V: MapVisitor>(&mut self, mut visitor: V) -> Result<Shape, V::Error> {
+        let mut fields = Vec::new();
+        while let Some(field) = try!(visitor.visit()) {
+            fields.push(field);
+        }
+        try!(visitor.end());
+        Shape::from_fields(fields).map_err(|message| de::Error::syntax(&message))
+    }
+}
+
+impl Encodable for Content {
+    fn encode<S: Encoder>(&self, encoder: &mut S) -> Result<(), S::Error> {
+        match *self {
+            Content::Str(ref value) => value.encode(encoder),
+            Content::F64(value) => value.encode(encoder),
+        }
+    }
+}
+
+impl Encodable for Shape {
+    fn encode<S: Encoder>(&self, encoder: &mut S) -> Result<(), S::Error> {
+        let fields = self.fields();
+        encoder.emit_map(fields.len(), |encoder| {
+            for (index, &(key, ref value)) in fields.iter().enumerate() {
+                try!(encoder.emit_map_elt_key(index, |encoder| key.encode(encoder)));
+                try!(encoder.emit_map_elt_val(index, |encoder| value.encode(encoder)));
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Decodable for Shape {
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<Shape, D::Error> {
+        decoder.read_map(|decoder, len| {
+            let mut fields = Vec::with_capacity(len);
+            for index in 0..len {
+                let key: String = try!(decoder.read_map_elt_key(index, Decodable::decode));
+                let value = try!(decoder.read_map_elt_val(index, |decoder| {
+                    if key == "type" {
+                        decoder.read_str().map(Content::Str)
+                    } else {
+                        decoder.read_f64().map(Content::F64)
+                    }
+                }));
+                fields.push((key, value));
+            }
+            Shape::from_fields(fields).map_err(|message| decoder.error(&message))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Shape;
+
+    #[test]
+    fn round_trips() {
+        let shapes = vec![Shape::Circle { r: 1.5 }, Shape::Square { s: 0.25 }];
+        // Only serde_and_bincode can't decode the buffered values.  rustc_and_bincode gets away
+        // with it because its impl knows every value's type up front.
+        let expected = [("rustc_and_cbor", true),
+                        ("serde_and_bincode", false),
+                        ("rustc_and_bincode", true),
+                        ("serde_and_json", true),
+                        ("rustc_and_cbor_canonical", true)];
+        let codecs = ::codec::all::<Vec<Shape>>();
+        assert_eq!(codecs.len(), expected.len());
+        for (codec, &(name, decodes)) in codecs.into_iter().zip(expected.iter()) {
+            assert_eq!(codec.name(), name);
+            let bytes = codec.encode(&shapes).unwrap();
+            match codec.decode(&bytes) {
+                Ok(decoded) => {
+                    assert!(decodes, "{} should reject internally tagged enums", name);
+                    assert_eq!(decoded, shapes);
+                }
+                Err(error) => assert!(!decodes, "{} failed to decode: {}", name, error),
+            }
+        }
+    }
+
+    #[test]
+    fn json_layout() {
+        let shapes = vec![Shape::Circle { r: 1.5 }, Shape::Square { s: 2.5 }];
+        let json = String::from_utf8(::serde_and_json::encode(&shapes)).unwrap();
+        assert_eq!(json, r#"[{"type":"c","r":1.5},{"type":"Square","s":2.5}]"#);
+        // The tag can come anywhere in the map.
+        let reordered = br#"[{"r":1.5,"type":"c"},{"s":2.5,"type":"Square"}]"#;
+        assert_eq!(::serde_and_json::decode::<Vec<Shape>>(reordered), shapes);
+    }
+}
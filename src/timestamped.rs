@@ -0,0 +1,187 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use rustc_serialize::{Decodable, Decoder, Encodable, Encoder};
+use serde::de;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use {Document, EvalError};
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// A point in time, encoded as whole seconds since the Unix epoch (negative before it) plus
+/// nanoseconds.  Neither library supports `SystemTime` itself.
+#[derive(Debug, PartialEq)]
+pub struct Timestamp(pub SystemTime);
+
+/// A span of time, encoded as whole seconds plus nanoseconds.
+#[derive(Debug, PartialEq)]
+pub struct Elapsed(pub Duration);
+
+impl Timestamp {
+    fn to_parts(&self) -> (i64, u32) {
+        match self.0.duration_since(UNIX_EPOCH) {
+            Ok(after) => (after.as_secs() as i64, after.subsec_nanos()),
+            Err(error) => {
+                let before = error.duration();
+                match before.subsec_nanos() {
+                    0 => (-(before.as_secs() as i64), 0),
+                    nanos => (-(before.as_secs() as i64) - 1, NANOS_PER_SEC - nanos),
+                }
+            }
+        }
+    }
+
+    /// Fails rather than panicking on parts which are well-formed but out of range.  Arithmetic on
+    /// `UNIX_EPOCH` panics on overflow, so the range is checked first.  On Unix, `SystemTime` holds
+    /// its seconds as an `i64`, so only stepping back `i64::min_value()` seconds can overflow.
+    fn from_parts(secs: i64, nanos: u32) -> Result<Timestamp, EvalError> {
+        try!(check_nanos(nanos));
+        if secs >= 0 {
+            Ok(Timestamp(UNIX_EPOCH + Duration::new(secs as u64, nanos)))
+        } else if secs == i64::min_value() {
+            Err(EvalError::TimeOutOfRange)
+        } else {
+            // Going back whole seconds leaves no nanoseconds, so adding them can't carry.
+            Ok(Timestamp(UNIX_EPOCH - Duration::new(-secs as u64, 0) + Duration::new(0, nanos)))
+        }
+    }
+}
+
+impl Elapsed {
+    fn from_parts(secs: u64, nanos: u32) -> Result<Elapsed, EvalError> {
+        // With whole nanoseconds there's no carry into the seconds to overflow them.
+        try!(check_nanos(nanos));
+        Ok(Elapsed(Duration::new(secs, nanos)))
+    }
+}
+
+fn check_nanos(nanos: u32) -> Result<(), EvalError> {
+    if nanos >= NANOS_PER_SEC {
+        return Err(EvalError::InvalidNanos(nanos));
+    }
+    Ok(())
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        self.to_parts().serialize(serializer)
+    }
+}
+
+impl Deserialize for Timestamp {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<Timestamp, D::Error> {
+        let (secs, nanos) = try!(Deserialize::deserialize(deserializer));
+        Timestamp::from_parts(secs, nanos).map_err(|error| de::Error::syntax(&error.to_string()))
+    }
+}
+
+impl Encodable for Timestamp {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), E::Error> {
+        self.to_parts().encode(encoder)
+    }
+}
+
+impl Decodable for Timestamp {
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<Timestamp, D::Error> {
+        let (secs, nanos) = try!(Decodable::decode(decoder));
+        Timestamp::from_parts(secs, nanos).map_err(|error| decoder.error(&error.to_string()))
+    }
+}
+
+impl Serialize for Elapsed {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        (self.0.as_secs(), self.0.subsec_nanos()).serialize(serializer)
+    }
+}
+
+impl Deserialize for Elapsed {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<Elapsed, D::Error> {
+        let (secs, nanos) = try!(Deserialize::deserialize(deserializer));
+        Elapsed::from_parts(secs, nanos).map_err(|error| de::Error::syntax(&error.to_string()))
+    }
+}
+
+impl Encodable for Elapsed {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), E::Error> {
+        (self.0.as_secs(), self.0.subsec_nanos()).encode(encoder)
+    }
+}
+
+impl Decodable for Elapsed {
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<Elapsed, D::Error> {
+        let (secs, nanos) = try!(Decodable::decode(decoder));
+        Elapsed::from_parts(secs, nanos).map_err(|error| decoder.error(&error.to_string()))
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct Timestamped {
+    created: Timestamp,
+    elapsed: Elapsed,
+    doc: Document,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use super::{Elapsed, Timestamp, Timestamped};
+    use {make_sample_data, Document};
+
+    fn make_timestamped(created: SystemTime) -> Timestamped {
+        Timestamped {
+            created: Timestamp(created),
+            elapsed: Elapsed(Duration::new(3, 999_999_999)),
+            doc: make_sample_data(0),
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        for codec in ::codec::all::<Timestamped>() {
+            for &created in &[SystemTime::now(),
+                              UNIX_EPOCH,
+                              UNIX_EPOCH - Duration::new(1, 1),
+                              UNIX_EPOCH - Duration::new(86400, 0)] {
+                let timestamped = make_timestamped(created);
+                let bytes = codec.encode(&timestamped).unwrap();
+                assert_eq!(codec.decode(&bytes).unwrap(), timestamped);
+            }
+        }
+    }
+
+    // Well-formed but out of range parts must fail to decode rather than panic.  Whether the
+    // extreme seconds fit depends on the platform's `SystemTime`, so only the nanoseconds are
+    // checked for an error.
+    #[test]
+    fn out_of_range() {
+        let timestamps = ::codec::all::<(i64, u32)>().into_iter().zip(::codec::all::<Timestamp>());
+        for (parts_codec, codec) in timestamps {
+            for &secs in &[i64::min_value(), i64::max_value()] {
+                let _ = codec.decode(&parts_codec.encode(&(secs, 999_999_999)).unwrap());
+            }
+            let bytes = parts_codec.encode(&(i64::min_value(), 0u32)).unwrap();
+            assert!(codec.decode(&bytes).is_err(), "{} accepted i64::MIN seconds", codec.name());
+            let bytes = parts_codec.encode(&(0i64, 1_000_000_000u32)).unwrap();
+            assert!(codec.decode(&bytes).is_err(), "{} accepted too many nanos", codec.name());
+        }
+        let elapsed = ::codec::all::<(u64, u32)>().into_iter().zip(::codec::all::<Elapsed>());
+        for (parts_codec, codec) in elapsed {
+            let bytes = parts_codec.encode(&(u64::max_value(), 999_999_999u32)).unwrap();
+            assert_eq!(codec.decode(&bytes).unwrap(),
+                       Elapsed(Duration::new(u64::max_value(), 999_999_999)));
+            let bytes = parts_codec.encode(&(u64::max_value(), 1_000_000_000u32)).unwrap();
+            assert!(codec.decode(&bytes).is_err(), "{} accepted too many nanos", codec.name());
+        }
+    }
+
+    #[test]
+    fn sizes() {
+        println!("");
+        println!("Size added by wrapping a small document with a timestamp and duration:");
+        let timestamped = make_timestamped(SystemTime::now());
+        let codecs = ::codec::all::<Timestamped>().into_iter().zip(::codec::all::<Document>());
+        for (timestamped_codec, document_codec) in codecs {
+            let with_times = timestamped_codec.encode(&timestamped).unwrap().len();
+            let without = document_codec.encode(&timestamped.doc).unwrap().len();
+            println!("    {}: {} bytes", timestamped_codec.name(), with_times - without);
+        }
+    }
+}
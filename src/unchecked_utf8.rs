@@ -0,0 +1,85 @@
+use std::str;
+use borrow_map::{read_bytes, read_capacity, read_u64};
+use person_ref::MIN_PERSON_SIZE;
+use {EvalError, Person};
+
+/// Decodes a bincode-encoded `Vec<Person>` by hand, building each string from its bytes with
+/// `to_string`.  serde 0.6's bincode validates inside `visit_string`, before a visitor could skip
+/// it, so both variants read the layout themselves and differ only in `to_string`.
+fn decode_people_with<F>(mut bytes: &[u8], to_string: F) -> Result<Vec<Person>, EvalError>
+    where F: Fn(&[u8]) -> Result<String, EvalError>
+{
+    let (len, capacity) = try!(read_capacity(&mut bytes, MIN_PERSON_SIZE));
+    let mut people = Vec::with_capacity(capacity);
+    for _ in 0..len {
+        let id = try!(read_u64(&mut bytes));
+        let name = try!(to_string(try!(read_bytes(&mut bytes))));
+        let email = try!(to_string(try!(read_bytes(&mut bytes))));
+        people.push(Person {
+            id: id,
+            name: name,
+            email: email,
+        });
+    }
+    Ok(people)
+}
+
+/// Decodes a bincode-encoded `Vec<Person>`, validating each string as UTF-8.
+pub fn decode_people_checked(bytes: &[u8]) -> Result<Vec<Person>, EvalError> {
+    decode_people_with(bytes, |bytes| Ok(try!(str::from_utf8(bytes)).to_owned()))
+}
+
+/// Decodes a bincode-encoded `Vec<Person>` without validating any strings.  Only use this on
+/// trusted data.
+pub unsafe fn decode_people_unchecked(bytes: &[u8]) -> Result<Vec<Person>, EvalError> {
+    decode_people_with(bytes, |bytes| Ok(String::from_utf8_unchecked(bytes.to_vec())))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str;
+    use super::{decode_people_checked, decode_people_unchecked};
+    use make_people;
+    use test::{black_box, Bencher};
+
+    #[test]
+    fn matches_serde() {
+        let people = make_people(1000);
+        let bytes = ::serde_and_bincode::encode(&people);
+        assert_eq!(decode_people_checked(&bytes).unwrap(), people);
+        assert_eq!(unsafe { decode_people_unchecked(&bytes) }.unwrap(), people);
+    }
+
+    #[test]
+    fn checked_rejects_invalid_utf8() {
+        let people = make_people(1);
+        let mut bytes = ::serde_and_bincode::encode(&people);
+        // The first byte of the name, after the count, id and name length.
+        bytes[24] = 0xff;
+        assert!(decode_people_checked(&bytes).is_err());
+    }
+
+    #[bench]
+    fn serde_and_bincode_decode_people_checked_utf8(bencher: &mut Bencher) {
+        let bytes = ::serde_and_bincode::encode(&make_people(10000));
+        bencher.iter(|| decode_people_checked(&bytes).unwrap())
+    }
+
+    #[bench]
+    fn serde_and_bincode_decode_people_unchecked_utf8(bencher: &mut Bencher) {
+        let bytes = ::serde_and_bincode::encode(&make_people(10000));
+        bencher.iter(|| unsafe { decode_people_unchecked(&bytes) }.unwrap())
+    }
+
+    // The validation on its own, for the same strings as the decode benches.
+    #[bench]
+    fn validate_people_utf8(bencher: &mut Bencher) {
+        let people = make_people(10000);
+        bencher.iter(|| {
+            for person in &people {
+                black_box(str::from_utf8(person.name.as_bytes()).unwrap());
+                black_box(str::from_utf8(person.email.as_bytes()).unwrap());
+            }
+        })
+    }
+}
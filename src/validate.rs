@@ -0,0 +1,136 @@
+use std::error::Error;
+use std::fmt;
+use codec::Codec;
+use {Document, EvalError, Person};
+
+/// Why a value was rejected by `decode_validated`.
+#[derive(Debug)]
+pub enum ValidationError {
+    Decode(EvalError),
+    ZeroDocumentId,
+    InvalidEmail {
+        person_id: u64,
+    },
+}
+
+impl From<EvalError> for ValidationError {
+    fn from(error: EvalError) -> ValidationError {
+        ValidationError::Decode(error)
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ValidationError::Decode(ref error) => write!(formatter, "Decode error: {}", error),
+            ValidationError::ZeroDocumentId => write!(formatter, "Document id is zero"),
+            ValidationError::InvalidEmail { person_id } => {
+                write!(formatter, "Person {} has an email address without an '@'", person_id)
+            }
+        }
+    }
+}
+
+impl Error for ValidationError {
+    fn description(&self) -> &str {
+        match *self {
+            ValidationError::Decode(ref error) => error.description(),
+            ValidationError::ZeroDocumentId => "zero document id",
+            ValidationError::InvalidEmail { .. } => "invalid email",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            ValidationError::Decode(ref error) => Some(error),
+            ValidationError::ZeroDocumentId |
+            ValidationError::InvalidEmail { .. } => None,
+        }
+    }
+}
+
+/// Invariants a value must uphold beyond being well-formed on the wire.
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationError>;
+}
+
+impl Validate for Person {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if !self.email.contains('@') {
+            return Err(ValidationError::InvalidEmail { person_id: self.id });
+        }
+        Ok(())
+    }
+}
+
+impl Validate for Document {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.id == 0 {
+            return Err(ValidationError::ZeroDocumentId);
+        }
+        for author in &self.authors {
+            try!(author.validate());
+        }
+        Ok(())
+    }
+}
+
+/// Decodes `bytes` with `codec`, then checks the result is valid.
+pub fn decode_validated<T: Validate>(codec: &Codec<T>, bytes: &[u8]) -> Result<T, ValidationError> {
+    let value = try!(codec.decode(bytes));
+    try!(value.validate());
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_validated, ValidationError};
+    use {make_people, make_sample_data, Document, Person};
+
+    #[test]
+    fn valid() {
+        let document = make_sample_data(1024);
+        for codec in ::codec::all::<Document>() {
+            let bytes = codec.encode(&document).unwrap();
+            assert_eq!(decode_validated(codec, &bytes).unwrap(), document);
+        }
+    }
+
+    #[test]
+    fn empty_email() {
+        let mut document = make_sample_data(1024);
+        document.authors[1].email = String::new();
+        for codec in ::codec::all::<Document>() {
+            let bytes = codec.encode(&document).unwrap();
+            // The document itself decodes fine; only validating it fails.
+            assert_eq!(codec.decode(&bytes).unwrap(), document);
+            assert_err!(decode_validated(codec, &bytes),
+                        ValidationError::InvalidEmail { person_id: 2 },
+                        codec.name());
+        }
+    }
+
+    #[test]
+    fn zero_id() {
+        let mut document = make_sample_data(1024);
+        document.id = 0;
+        for codec in ::codec::all::<Document>() {
+            let bytes = codec.encode(&document).unwrap();
+            assert_err!(decode_validated(codec, &bytes),
+                        ValidationError::ZeroDocumentId,
+                        codec.name());
+        }
+    }
+
+    #[test]
+    fn undecodable() {
+        let person = make_people(1).pop().unwrap();
+        for codec in ::codec::all::<Person>() {
+            let bytes = codec.encode(&person).unwrap();
+            assert_eq!(decode_validated(codec, &bytes).unwrap(), person);
+            assert_err!(decode_validated::<Person>(codec, &bytes[..bytes.len() / 2]),
+                        ValidationError::Decode(_),
+                        codec.name());
+        }
+    }
+}
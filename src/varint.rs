@@ -0,0 +1,102 @@
+use EvalError;
+
+// A `u64` needs at most ten 7-bit groups, the last holding only the top bit.
+const MAX_LEN: usize = 10;
+
+/// Appends `v` to `bytes` as an unsigned LEB128 varint.
+pub fn write_u64(bytes: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        bytes.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    bytes.push(v as u8);
+}
+
+/// Reads an unsigned LEB128 varint from the front of `bytes`, returning it and the number of bytes
+/// it occupied.
+pub fn read_u64(bytes: &[u8]) -> Result<(u64, usize), EvalError> {
+    let mut v = 0u64;
+    for (i, &byte) in bytes.iter().take(MAX_LEN).enumerate() {
+        if i == MAX_LEN - 1 && byte > 1 {
+            return Err(EvalError::InvalidVarint);
+        }
+        v |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte < 0x80 {
+            return Ok((v, i + 1));
+        }
+    }
+    Err(EvalError::TruncatedVarint)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::u64;
+    use byteorder::{BigEndian, WriteBytesExt};
+    use super::{read_u64, write_u64, MAX_LEN};
+    use EvalError;
+    use test::Bencher;
+
+    fn encoded(v: u64) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_u64(&mut bytes, v);
+        bytes
+    }
+
+    #[test]
+    fn boundaries() {
+        assert_eq!(encoded(0), [0x00]);
+        assert_eq!(encoded(127), [0x7f]);
+        assert_eq!(encoded(128), [0x80, 0x01]);
+        assert_eq!(encoded(u64::MAX),
+                   [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]);
+        for &v in &[0, 127, 128, u64::MAX] {
+            let bytes = encoded(v);
+            assert_eq!(read_u64(&bytes).unwrap(), (v, bytes.len()));
+        }
+    }
+
+    #[test]
+    fn trailing_bytes() {
+        assert_eq!(read_u64(&[0x80, 0x01, 0xff]).unwrap(), (128, 2));
+    }
+
+    #[test]
+    fn errors() {
+        match read_u64(&[0x80, 0x80]) {
+            Err(EvalError::TruncatedVarint) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match read_u64(&[0xff; MAX_LEN]) {
+            Err(EvalError::InvalidVarint) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    fn small_values() -> Vec<u64> {
+        (0..10000).map(|i| i % 300).collect()
+    }
+
+    #[bench]
+    fn encode_small_u64s_as_varints(bencher: &mut Bencher) {
+        let values = small_values();
+        bencher.iter(|| {
+            let mut bytes = Vec::with_capacity(values.len() * MAX_LEN);
+            for &v in &values {
+                write_u64(&mut bytes, v);
+            }
+            bytes
+        })
+    }
+
+    #[bench]
+    fn encode_small_u64s_as_fixed(bencher: &mut Bencher) {
+        let values = small_values();
+        bencher.iter(|| {
+            let mut bytes = Vec::with_capacity(values.len() * 8);
+            for &v in &values {
+                bytes.write_u64::<BigEndian>(v).unwrap();
+            }
+            bytes
+        })
+    }
+}
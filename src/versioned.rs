@@ -0,0 +1,83 @@
+use rustc_serialize::{Decodable, Encodable};
+use serde::{Deserialize, Serialize};
+use {adaptive, codec, EvalError};
+
+/// Identifies the envelope format.  Chosen to be unlikely at the start of any codec's output.
+pub const MAGIC: [u8; 4] = [0xd5, b'S', b'E', b'V'];
+
+const HEADER_LEN: usize = 6;
+
+/// Encodes `v` with the codec identified by `codec_id`, preceded by a header of `MAGIC`, the codec
+/// id and `version`.
+///
+/// Panics if `codec_id` isn't one of `codec::all()` or if encoding fails.
+pub fn encode<T>(codec_id: u8, version: u8, v: &T) -> Vec<u8>
+    where T: Encodable + Decodable + Serialize + Deserialize + 'static
+{
+    let codec = codec::all::<T>().into_iter().find(|codec| codec.id() == codec_id).unwrap();
+    let payload = codec.encode(v).unwrap();
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(&MAGIC);
+    bytes.push(codec_id);
+    bytes.push(version);
+    bytes.extend_from_slice(&payload);
+    bytes
+}
+
+/// Decodes output of `encode`, rejecting it unless it was written with format `version`.
+pub fn decode<T>(bytes: &[u8], version: u8) -> Result<T, EvalError>
+    where T: Encodable + Decodable + Serialize + Deserialize + 'static
+{
+    if bytes.len() < HEADER_LEN {
+        return Err(EvalError::TruncatedHeader);
+    }
+    if bytes[..MAGIC.len()] != MAGIC {
+        return Err(EvalError::BadMagic);
+    }
+    let codec_id = bytes[4];
+    if bytes[5] != version {
+        return Err(EvalError::UnsupportedVersion {
+            expected: version,
+            got: bytes[5],
+        });
+    }
+    adaptive::decode_tagged(codec_id, &bytes[HEADER_LEN..], &codec::all())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode};
+    use {codec, make_sample_data, Document, EvalError};
+
+    #[test]
+    fn round_trips() {
+        let document = make_sample_data(1024);
+        for codec in codec::all::<Document>() {
+            let bytes = encode(codec.id(), 1, &document);
+            assert_eq!(&bytes[6..], &codec.encode(&document).unwrap()[..]);
+            assert_eq!(decode::<Document>(&bytes, 1).unwrap(), document);
+        }
+    }
+
+    #[test]
+    fn wrong_magic() {
+        let mut bytes = encode(1, 1, &make_sample_data(0));
+        bytes[0] ^= 0xff;
+        assert_err!(decode::<Document>(&bytes, 1), EvalError::BadMagic);
+        assert_err!(decode::<Document>(&bytes[..5], 1), EvalError::TruncatedHeader);
+    }
+
+    #[test]
+    fn version_mismatch() {
+        let bytes = encode(1, 2, &make_sample_data(0));
+        assert_err!(decode::<Document>(&bytes, 1),
+                    EvalError::UnsupportedVersion { expected: 1, got: 2 });
+    }
+
+    #[test]
+    fn unknown_codec() {
+        let mut bytes = encode(1, 1, &make_sample_data(0));
+        bytes[4] = 200;
+        assert_err!(decode::<Document>(&bytes, 1), EvalError::UnknownCodec(200));
+    }
+}
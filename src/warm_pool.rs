@@ -0,0 +1,62 @@
+use test::black_box;
+
+const PAGE_SIZE: usize = 4096;
+
+/// Allocates `blocks` buffers of `block_size` bytes, writes to every page of each and frees them
+/// again, so that later allocations of up to that size can reuse memory which is already faulted
+/// in rather than paying for first-touch page faults.
+pub fn warm_pool(block_size: usize, blocks: usize) {
+    let mut pool = Vec::with_capacity(blocks);
+    for _ in 0..blocks {
+        let mut block = Vec::<u8>::with_capacity(block_size);
+        unsafe {
+            block.set_len(block_size);
+        }
+        let mut i = 0;
+        while i < block_size {
+            block[i] = 1;
+            i += PAGE_SIZE;
+        }
+        pool.push(black_box(block));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::warm_pool;
+    use bench::bench_encode;
+    use codec::{self, Codec};
+    use {make_sample_data, Document};
+    use test::Bencher;
+
+    // Room for each codec's output buffer as it doubles up to the size of the encoded document.
+    const BLOCKS: usize = 8;
+
+    // Compare against the `*_encode_big` benches to see how much first-touch page faults cost.
+    fn bench_encode_big_warm_pool<C: Codec<Document>>(bencher: &mut Bencher, codec: &C) {
+        let document = make_sample_data(1024 * 1024);
+        let len = codec.encode(&document).unwrap().len();
+        warm_pool(len.next_power_of_two() * 2, BLOCKS);
+        bench_encode(bencher, codec, document);
+    }
+
+    #[bench]
+    fn rustc_and_cbor_encode_big_warm_pool(bencher: &mut Bencher) {
+        bench_encode_big_warm_pool(bencher, &codec::RustcAndCbor);
+    }
+
+    #[bench]
+    fn serde_and_bincode_encode_big_warm_pool(bencher: &mut Bencher) {
+        bench_encode_big_warm_pool(bencher, &codec::SerdeAndBincode);
+    }
+
+    #[bench]
+    fn rustc_and_bincode_encode_big_warm_pool(bencher: &mut Bencher) {
+        bench_encode_big_warm_pool(bencher, &codec::RustcAndBincode);
+    }
+
+    #[bench]
+    fn serde_and_json_encode_big_warm_pool(bencher: &mut Bencher) {
+        bench_encode_big_warm_pool(bencher, &codec::SerdeAndJson);
+    }
+}
@@ -0,0 +1,120 @@
+use std::marker::PhantomData;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A unit struct, such as a typestate marker.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct Unit;
+
+/// What a `Tagged<T>` should look like on the wire.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct Plain {
+    pub value: u64,
+}
+
+/// A value carrying a typestate marker which has no runtime representation.
+///
+/// serde 0.6 has no impls for `PhantomData`, so the serde impls are written by hand and simply
+/// serialize a `Plain`.  rustc-serialize does have impls, so those are derived: the marker is
+/// encoded as a nil, but decoded without reading anything.
+#[derive(Debug, PartialEq, RustcDecodable, RustcEncodable)]
+pub struct Tagged<T> {
+    pub value: u64,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Tagged<T> {
+    pub fn new(value: u64) -> Tagged<T> {
+        Tagged {
+            value: value,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Serialize for Tagged<T> {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        Plain { value: self.value }.serialize(serializer)
+    }
+}
+
+impl<T> Deserialize for Tagged<T> {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<Tagged<T>, D::Error> {
+        let plain = try!(Plain::deserialize(deserializer));
+        Ok(Tagged::new(plain.value))
+    }
+}
+
+/// A struct made up entirely of zero-sized fields.
+#[derive(Debug, PartialEq, Deserialize, Serialize, RustcDecodable, RustcEncodable)]
+pub struct AllZst {
+    pub unit: Unit,
+    pub nothing: (),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+    use rustc_serialize::{Decodable, Encodable};
+    use serde::{Deserialize, Serialize};
+    use super::{AllZst, Plain, Tagged, Unit};
+
+    fn sizes<T>(value: &T) -> Vec<usize>
+        where T: Debug + PartialEq + Encodable + Decodable + Serialize + Deserialize
+    {
+        ::codec::all::<T>()
+            .into_iter()
+            .map(|codec| {
+                let bytes = codec.encode(value).unwrap();
+                assert_eq!(codec.decode(&bytes).unwrap(), *value);
+                bytes.len()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sizes_match_equivalents() {
+        // Sizes are in `codec::all` order: rustc_and_cbor, serde_and_bincode, rustc_and_bincode,
+        // serde_and_json and rustc_and_cbor_canonical.
+
+        // A unit struct costs the same as `()`: nothing in bincode, `null` in JSON, and a single
+        // byte in CBOR, albeit an empty map rather than a null.
+        assert_eq!(sizes(&Unit), sizes(&()));
+        assert_eq!(sizes(&Unit), [1, 0, 0, 4, 1]);
+
+        // The marker is free except in CBOR, where the derived impl writes `"_marker": null`.
+        let plain = sizes(&Plain { value: 1 });
+        let tagged = sizes(&Tagged::<Unit>::new(1));
+        assert_eq!(plain, [8, 8, 8, 11, 8]);
+        assert_eq!(tagged, [17, 8, 8, 11, 17]);
+
+        // Only bincode gets an all-ZST struct down to nothing.  The other codecs still pay for each
+        // field's name.
+        let all_zst = AllZst {
+            unit: Unit,
+            nothing: (),
+        };
+        assert_eq!(sizes(&all_zst), [16, 0, 0, 28, 16]);
+    }
+
+    // Decoding the marker doesn't consume the nil CBOR wrote for it, which is then mistaken for the
+    // next element of a sequence.
+    #[test]
+    fn cbor_leaves_marker_behind() {
+        let tagged = vec![Tagged::<Unit>::new(1), Tagged::new(2)];
+        for codec in ::codec::all::<Vec<Tagged<Unit>>>() {
+            let bytes = codec.encode(&tagged).unwrap();
+            match codec.decode(&bytes) {
+                Ok(decoded) => {
+                    assert!(!codec.name().contains("cbor"), "{} should fail", codec.name());
+                    assert_eq!(decoded, tagged);
+                }
+                Err(error) => {
+                    assert!(codec.name().contains("cbor"),
+                            "{} failed to decode: {}",
+                            codec.name(),
+                            error)
+                }
+            }
+        }
+    }
+}